@@ -58,3 +58,2352 @@ fn test_invert_hsv() {
     assert_eq!(color1.invert().invert().unwrap_hsv(), color1.unwrap_hsv());
     assert_eq!(color2.invert().invert().unwrap_hsv(), color2.unwrap_hsv());
 }
+
+#[test]
+fn test_parse_hex() {
+    assert_eq!(Color::parse("#000000").unwrap(), Color::new_rgb(0, 0, 0));
+    assert_eq!(Color::parse("#FF01ff").unwrap(), Color::new_rgb(255, 1, 255));
+    assert_eq!(Color::parse("#f0a").unwrap(), Color::new_rgb(255, 0, 170));
+    assert_eq!(Color::parse("#01147bff").unwrap(), Color::new_rgba(1, 20, 123, 255));
+    assert_eq!("  #01147b\n".parse::<Color>().unwrap(), Color::new_rgb(1, 20, 123));
+}
+
+#[test]
+fn test_parse_functions() {
+    assert_eq!(Color::parse("rgb(1,20,123)").unwrap(), Color::new_rgb(1, 20, 123));
+    assert_eq!(Color::parse("RGB( 255 , 0, 100% )").unwrap(), Color::new_rgb(255, 0, 255));
+    assert_eq!(Color::parse("rgb(0%,50%,100%)").unwrap(), Color::new_rgb(0, 128, 255));
+
+    assert_eq!(Color::parse("hsv(90,3%,99%)").unwrap(), Color::new_hsv(90, 3, 99));
+    assert_eq!(Color::parse("Hsv( 360, 100 ,100% )").unwrap(), Color::new_hsv(360, 100, 100));
+
+    assert_eq!(Color::parse("hsl(0,100%,50%)").unwrap().to_hsv(), Color::new_hsv(0, 100, 100));
+    assert_eq!(Color::parse("hsl(120,100%,25%)").unwrap().to_hsv(), Color::new_hsv(120, 100, 50));
+    assert_eq!(Color::parse("hsl(240,0%,100%)").unwrap().to_hsv(), Color::new_hsv(240, 0, 100));
+}
+
+#[test]
+fn test_parse_errors() {
+    fn error(position: usize, reason: ParseColorReason) -> Result<Color, ParseColorError> {
+        Err(ParseColorError { position, reason })
+    }
+
+    assert_eq!(Color::parse(""), error(0, ParseColorReason::Empty));
+    assert_eq!(Color::parse("   "), error(3, ParseColorReason::Empty));
+    assert_eq!(Color::parse(" red"), error(1, ParseColorReason::UnknownFormat));
+    assert_eq!(Color::parse("#12345"), error(1, ParseColorReason::InvalidHexLength));
+    assert_eq!(Color::parse("#12g456"), error(3, ParseColorReason::InvalidHexDigit));
+    assert_eq!(Color::parse("#01147b8"), error(1, ParseColorReason::InvalidHexLength));
+    assert_eq!(Color::parse("hsv(361,0%,0%)"), error(4, ParseColorReason::OutOfRange));
+    assert_eq!(Color::parse("hsv(0, 0%, 101%)"), error(11, ParseColorReason::OutOfRange));
+    assert_eq!(Color::parse("rgb(256,0,0)"), error(4, ParseColorReason::OutOfRange));
+    assert_eq!(Color::parse("rgb(-1,0,0)"), error(4, ParseColorReason::InvalidNumber));
+    assert_eq!(Color::parse("rgb(1,2;3)"), error(7, ParseColorReason::Expected(',')));
+    assert_eq!(Color::parse("rgb(1,2"), error(7, ParseColorReason::Expected(',')));
+    assert_eq!(Color::parse("rgb(1,2,3"), error(9, ParseColorReason::Expected(')')));
+    assert_eq!(Color::parse("rgb(1,2,3) x"), error(11, ParseColorReason::TrailingCharacters));
+    assert_eq!(Color::parse("rgb(1,0 2,3)"), error(8, ParseColorReason::Expected(',')));
+}
+
+#[test]
+fn test_parse_round_trip() {
+    for red in (0..=255).step_by(15) {
+        for green in (0..=255).step_by(15) {
+            for blue in 0..=255 {
+                let color = Color::new_rgb(red, green, blue);
+                assert_eq!(Color::parse(&color.to_string()), Ok(color));
+            }
+        }
+    }
+
+    for hue in 0..=360 {
+        for saturation in (0..=100).step_by(5) {
+            for value in (0..=100).step_by(5) {
+                let color = Color::new_hsv(hue, saturation, value);
+                assert_eq!(color.to_string().parse::<Color>(), Ok(color));
+            }
+        }
+    }
+}
+
+fn assert_rgb_close(left: (u8, u8, u8), right: (u8, u8, u8), tolerance: i16) {
+    let (l, r) = ([left.0, left.1, left.2], [right.0, right.1, right.2]);
+    for i in 0..3 {
+        assert!((l[i] as i16 - r[i] as i16).abs() <= tolerance, "{:?} vs {:?}", left, right);
+    }
+}
+
+#[test]
+fn test_hsv_to_rgb() {
+    assert_eq!(Color::new_hsv(0, 0, 0).to_rgb(), Color::new_rgb(0, 0, 0));
+    assert_eq!(Color::new_hsv(0, 100, 100).to_rgb(), Color::new_rgb(255, 0, 0));
+    assert_eq!(Color::new_hsv(360, 100, 100).to_rgb(), Color::new_rgb(255, 0, 0));
+    assert_eq!(Color::new_hsv(120, 100, 50).to_rgb(), Color::new_rgb(0, 128, 0));
+    assert_eq!(Color::new_hsv(240, 50, 100).to_rgb(), Color::new_rgb(128, 128, 255));
+    assert_eq!(Color::new_hsv(90, 3, 99).to_rgb(), Color::new_rgb(249, 252, 245));
+    assert_eq!(Color::new_hsv(300, 0, 20).to_rgb(), Color::new_rgb(51, 51, 51));
+
+    assert_eq!(Color::new_rgb(1, 20, 123).to_rgb(), Color::new_rgb(1, 20, 123));
+}
+
+#[test]
+fn test_rgb_to_hsv() {
+    assert_eq!(Color::new_rgb(0, 0, 0).to_hsv(), Color::new_hsv(0, 0, 0));
+    assert_eq!(Color::new_rgb(255, 255, 255).to_hsv(), Color::new_hsv(0, 0, 100));
+    assert_eq!(Color::new_rgb(255, 255, 0).to_hsv(), Color::new_hsv(60, 100, 100));
+    assert_eq!(Color::new_rgb(128, 128, 128).to_hsv(), Color::new_hsv(0, 0, 50));
+    assert_eq!(Color::new_rgb(1, 20, 123).to_hsv(), Color::new_hsv(231, 99, 48));
+    assert_eq!(Color::new_rgb(255, 0, 1).to_hsv(), Color::new_hsv(0, 100, 100));
+
+    assert_eq!(Color::new_hsv(90, 3, 99).to_hsv(), Color::new_hsv(90, 3, 99));
+}
+
+#[test]
+fn test_conversion_into_tuples() {
+    let (red, green, blue): (u8, u8, u8) = Color::new_hsv(0, 100, 100).into();
+    assert_eq!((red, green, blue), (255, 0, 0));
+    let rgb: (u8, u8, u8) = Color::new_rgb(1, 20, 123).into();
+    assert_eq!(rgb, (1, 20, 123));
+
+    let (hue, saturation, value): (u16, u8, u8) = Color::new_rgb(255, 255, 0).into();
+    assert_eq!((hue, saturation, value), (60, 100, 100));
+    let hsv: (u16, u8, u8) = Color::new_hsv(90, 3, 99).into();
+    assert_eq!(hsv, (90, 3, 99));
+}
+
+#[test]
+fn test_conversion_round_trip() {
+    for hue in 0..=360 {
+        for saturation in 0..=100 {
+            for value in (0..=100).step_by(3) {
+                let rgb = Color::new_hsv(hue, saturation, value).to_rgb();
+                assert_rgb_close(rgb.to_hsv().to_rgb().unwrap_rgb(), rgb.unwrap_rgb(), 1);
+            }
+        }
+    }
+
+    for red in (0..=255).step_by(5) {
+        for green in (0..=255).step_by(5) {
+            for blue in (0..=255).step_by(5) {
+                let rgb = Color::new_rgb(red, green, blue);
+                assert_rgb_close(rgb.to_hsv().to_rgb().unwrap_rgb(), rgb.unwrap_rgb(), 3);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_try_new_hsv() {
+    assert_eq!(Color::try_new_hsv(0, 0, 0), Ok(Color::new_hsv(0, 0, 0)));
+    assert_eq!(Color::try_new_hsv(360, 100, 100), Ok(Color::new_hsv(360, 100, 100)));
+
+    assert_eq!(Color::try_new_hsv(361, 0, 0), Err(ColorError::HueOutOfRange(361)));
+    assert_eq!(Color::try_new_hsv(100, 200, 0), Err(ColorError::SaturationOutOfRange(200)));
+    assert_eq!(Color::try_new_hsv(200, 100, 255), Err(ColorError::ValueOutOfRange(255)));
+    assert_eq!(Color::try_new_hsv(400, 101, 101), Err(ColorError::HueOutOfRange(400)));
+    assert_eq!(Color::try_new_hsv(0, 101, 101), Err(ColorError::SaturationOutOfRange(101)));
+}
+
+#[test]
+fn test_try_unwrap() {
+    let rgb = Color::new_rgb(1, 20, 123);
+    let hsv = Color::new_hsv(90, 3, 99);
+
+    assert_eq!(rgb.try_rgb(), Ok((1, 20, 123)));
+    assert_eq!(hsv.try_hsv(), Ok((90, 3, 99)));
+    assert_eq!(rgb.try_hsv(), Err(ColorError::NotHsv));
+    assert_eq!(hsv.try_rgb(), Err(ColorError::NotRgb));
+
+    assert!(catch_unwind(|| rgb.unwrap_hsv()).is_err());
+    assert!(catch_unwind(|| hsv.unwrap_rgb()).is_err());
+}
+
+#[test]
+fn test_color_error_is_an_error() {
+    fn checked(hue: u16) -> Result<Color, Box<dyn std::error::Error>> {
+        Ok(Color::try_new_hsv(hue, 50, 50)?)
+    }
+
+    assert!(checked(360).is_ok());
+    let error = checked(361).unwrap_err();
+    assert!(!error.to_string().is_empty());
+}
+
+#[test]
+fn test_alpha_constructors() {
+    assert_eq!(Color::new_rgba(1, 20, 123, 128).unwrap_rgba(), (1, 20, 123, 128));
+    assert_eq!(Color::new_hsva(90, 3, 99, 50).unwrap_hsva(), (90, 3, 99, 50));
+
+    assert_eq!(Color::try_new_hsva(360, 100, 100, 100), Ok(Color::new_hsva(360, 100, 100, 100)));
+    assert_eq!(Color::try_new_hsva(361, 0, 0, 0), Err(ColorError::HueOutOfRange(361)));
+    assert_eq!(Color::try_new_hsva(0, 0, 101, 101), Err(ColorError::ValueOutOfRange(101)));
+    assert_eq!(Color::try_new_hsva(0, 0, 0, 101), Err(ColorError::AlphaOutOfRange(101)));
+    assert!(catch_unwind(|| Color::new_hsva(0, 0, 0, 255)).is_err());
+
+    assert_eq!(Color::new_rgba(1, 20, 123, 128).try_rgb(), Err(ColorError::NotRgb));
+    assert_eq!(Color::new_rgb(1, 20, 123).try_rgba(), Err(ColorError::NotRgba));
+    assert_eq!(Color::new_hsv(90, 3, 99).try_hsva(), Err(ColorError::NotHsva));
+    assert!(catch_unwind(|| Color::new_rgba(0, 0, 0, 0).unwrap_rgb()).is_err());
+    assert!(catch_unwind(|| Color::new_hsv(0, 0, 0).unwrap_hsva()).is_err());
+}
+
+#[test]
+fn test_alpha_accessor() {
+    assert_eq!(Color::new_rgb(1, 20, 123).alpha(), 255);
+    assert_eq!(Color::new_hsv(90, 3, 99).alpha(), 255);
+    assert_eq!(Color::new_rgba(1, 20, 123, 0).alpha(), 0);
+    assert_eq!(Color::new_rgba(1, 20, 123, 77).alpha(), 77);
+    assert_eq!(Color::new_hsva(90, 3, 99, 50).alpha(), 128);
+    assert_eq!(Color::new_hsva(90, 3, 99, 100).alpha(), 255);
+}
+
+#[test]
+fn test_alpha_display() {
+    assert_eq!(Color::new_rgba(0, 0, 0, 0).to_string(), String::from("#00000000"));
+    assert_eq!(Color::new_rgba(255, 1, 255, 128).to_string(), String::from("#ff01ff80"));
+    assert_eq!(Color::new_hsva(0, 0, 0, 0).to_string(), String::from("hsva(0,0%,0%,0%)"));
+    assert_eq!(Color::new_hsva(90, 3, 99, 50).to_string(), String::from("hsva(90,3%,99%,50%)"));
+}
+
+#[test]
+fn test_alpha_parse() {
+    assert_eq!(Color::parse("#ff01ff80").unwrap(), Color::new_rgba(255, 1, 255, 128));
+    assert_eq!(Color::parse("#F0A8").unwrap(), Color::new_rgba(255, 0, 170, 136));
+    assert_eq!(Color::parse("rgba(255, 1, 255, 0.5)").unwrap(), Color::new_rgba(255, 1, 255, 128));
+    assert_eq!(Color::parse("rgba(0,0,0,.25)").unwrap(), Color::new_rgba(0, 0, 0, 64));
+    assert_eq!(Color::parse("rgba(0,0,0,1)").unwrap(), Color::new_rgba(0, 0, 0, 255));
+    assert_eq!(Color::parse("rgba(0,0,0,50%)").unwrap(), Color::new_rgba(0, 0, 0, 128));
+    assert_eq!(Color::parse("hsva(90,3%,99%,50%)").unwrap(), Color::new_hsva(90, 3, 99, 50));
+    assert_eq!(Color::parse("hsva(90,3,99,0.5)").unwrap(), Color::new_hsva(90, 3, 99, 50));
+    assert_eq!(Color::parse("hsla(120,100%,25%,0)").unwrap(), Color::new_hsva(120, 100, 50, 0));
+
+    let error = |position, reason| Err(ParseColorError { position, reason });
+    assert_eq!(Color::parse("rgba(0,0,0,1.5)"), error(11, ParseColorReason::OutOfRange));
+    assert_eq!(Color::parse("rgba(0,0,0,101%)"), error(11, ParseColorReason::OutOfRange));
+    assert_eq!(Color::parse("rgba(0,0,0,1.)"), error(11, ParseColorReason::InvalidNumber));
+    assert_eq!(Color::parse("rgba(0,0,0,0.5%)"), error(11, ParseColorReason::InvalidNumber));
+    assert_eq!(Color::parse("rgba(0,0,0)"), error(10, ParseColorReason::Expected(',')));
+    assert_eq!(Color::parse("rgb(0,0,0,1)"), error(9, ParseColorReason::Expected(')')));
+}
+
+#[test]
+fn test_alpha_parse_round_trip() {
+    for alpha in 0..=255 {
+        for value in (0..=255).step_by(51) {
+            let color = Color::new_rgba(value, 255 - value, value / 2, alpha);
+            assert_eq!(Color::parse(&color.to_string()), Ok(color));
+        }
+    }
+    for alpha in 0..=100 {
+        for hue in (0..=360).step_by(24) {
+            let color = Color::new_hsva(hue, alpha, 100 - alpha, alpha);
+            assert_eq!(Color::parse(&color.to_string()), Ok(color));
+        }
+    }
+}
+
+#[test]
+fn test_alpha_conversions() {
+    assert_eq!(Color::new_hsva(0, 100, 100, 50).to_rgb(), Color::new_rgba(255, 0, 0, 128));
+    assert_eq!(Color::new_rgba(255, 255, 0, 64).to_hsv(), Color::new_hsva(60, 100, 100, 25));
+    assert_eq!(Color::new_rgba(1, 20, 123, 64).to_rgb(), Color::new_rgba(1, 20, 123, 64));
+    assert_eq!(Color::new_hsv(0, 100, 100).to_rgb(), Color::new_rgb(255, 0, 0));
+
+    let rgb: (u8, u8, u8) = Color::new_hsva(0, 100, 100, 0).into();
+    assert_eq!(rgb, (255, 0, 0));
+    let hsv: (u16, u8, u8) = Color::new_rgba(255, 255, 0, 0).into();
+    assert_eq!(hsv, (60, 100, 100));
+    let rgba: (u8, u8, u8, u8) = Color::new_hsv(0, 100, 100).into();
+    assert_eq!(rgba, (255, 0, 0, 255));
+    let rgba: (u8, u8, u8, u8) = Color::new_hsva(0, 100, 100, 20).into();
+    assert_eq!(rgba, (255, 0, 0, 51));
+}
+
+#[test]
+fn test_alpha_invert() {
+    let overlay = Color::new_rgba(0, 255, 0, 64);
+    assert_eq!(overlay.invert(), Color::new_rgba(255, 0, 255, 64));
+    assert_eq!(overlay.invert_with(AlphaMode::Preserve), Color::new_rgba(255, 0, 255, 64));
+    assert_eq!(overlay.invert_with(AlphaMode::Invert), Color::new_rgba(255, 0, 255, 191));
+
+    let overlay = Color::new_hsva(120, 90, 35, 30);
+    assert_eq!(overlay.invert(), Color::new_hsva(240, 10, 65, 30));
+    assert_eq!(overlay.invert_with(AlphaMode::Invert), Color::new_hsva(240, 10, 65, 70));
+
+    assert_eq!(Color::new_rgb(120, 90, 135).invert_with(AlphaMode::Invert), Color::new_rgb(135, 165, 120));
+    assert_eq!(Color::new_hsv(120, 90, 35).invert_with(AlphaMode::Invert), Color::new_hsv(240, 10, 65));
+}
+
+#[test]
+fn test_hsl_hwb_constructors() {
+    assert_eq!(Color::new_hsl(120, 100, 50).unwrap_hsl(), (120, 100, 50));
+    assert_eq!(Color::new_hwb(120, 70, 70).unwrap_hwb(), (120, 70, 70));
+
+    assert_eq!(Color::try_new_hsl(361, 0, 0), Err(ColorError::HueOutOfRange(361)));
+    assert_eq!(Color::try_new_hsl(0, 101, 101), Err(ColorError::SaturationOutOfRange(101)));
+    assert_eq!(Color::try_new_hsl(0, 0, 101), Err(ColorError::LightnessOutOfRange(101)));
+    assert_eq!(Color::try_new_hwb(361, 0, 0), Err(ColorError::HueOutOfRange(361)));
+    assert_eq!(Color::try_new_hwb(0, 101, 101), Err(ColorError::WhitenessOutOfRange(101)));
+    assert_eq!(Color::try_new_hwb(0, 100, 101), Err(ColorError::BlacknessOutOfRange(101)));
+    assert!(catch_unwind(|| Color::new_hsl(0, 0, 200)).is_err());
+    assert!(catch_unwind(|| Color::new_hwb(0, 200, 0)).is_err());
+
+    assert_eq!(Color::new_hsl(0, 0, 0).try_hwb(), Err(ColorError::NotHwb));
+    assert_eq!(Color::new_hwb(0, 0, 0).try_hsl(), Err(ColorError::NotHsl));
+    assert_eq!(Color::new_hsl(0, 0, 0).try_hsv(), Err(ColorError::NotHsv));
+    assert!(catch_unwind(|| Color::new_hsv(0, 0, 0).unwrap_hsl()).is_err());
+    assert!(catch_unwind(|| Color::new_rgb(0, 0, 0).unwrap_hwb()).is_err());
+}
+
+#[test]
+fn test_hsl_hwb_display() {
+    assert_eq!(Color::new_hsl(0, 0, 0).to_string(), String::from("hsl(0 0% 0%)"));
+    assert_eq!(Color::new_hsl(120, 100, 50).to_string(), String::from("hsl(120 100% 50%)"));
+    assert_eq!(Color::new_hwb(360, 100, 100).to_string(), String::from("hwb(360 100% 100%)"));
+    assert_eq!(Color::new_hwb(90, 3, 9).to_string(), String::from("hwb(90 3% 9%)"));
+}
+
+#[test]
+fn test_space_separated_parse() {
+    assert_eq!(Color::parse("hsl(120 100% 50%)").unwrap(), Color::new_hsl(120, 100, 50));
+    assert_eq!(Color::parse("hsl(120,100%,50%)").unwrap(), Color::new_hsl(120, 100, 50));
+    assert_eq!(Color::parse("HWB( 90  3%\t9% )").unwrap(), Color::new_hwb(90, 3, 9));
+    assert_eq!(Color::parse("hwb(120, 0%, 0%)").unwrap(), Color::new_hwb(120, 0, 0));
+    assert_eq!(Color::parse("rgb(1 20 123)").unwrap(), Color::new_rgb(1, 20, 123));
+    assert_eq!(Color::parse("rgba(1 20 123)").unwrap(), Color::new_rgb(1, 20, 123));
+    assert_eq!(Color::parse("hsv(90 3% 99%)").unwrap(), Color::new_hsv(90, 3, 99));
+
+    assert_eq!(Color::parse("rgb(255 0 0 / 0.5)").unwrap(), Color::new_rgba(255, 0, 0, 128));
+    assert_eq!(Color::parse("rgb(255 0 0/50%)").unwrap(), Color::new_rgba(255, 0, 0, 128));
+    assert_eq!(Color::parse("hsv(90 3% 99% / 1)").unwrap(), Color::new_hsva(90, 3, 99, 100));
+    assert_eq!(Color::parse("hsl(120 100% 25% / 0)").unwrap(), Color::new_hsva(120, 100, 50, 0));
+    assert_eq!(Color::parse("hwb(0 0% 0% / 50%)").unwrap(), Color::new_hsva(0, 100, 100, 50));
+
+    let error = |position, reason| Err(ParseColorError { position, reason });
+    assert_eq!(Color::parse("rgb(1 0,2,3)"), error(7, ParseColorReason::Expected(' ')));
+    assert_eq!(Color::parse("rgb(1 2 3,4)"), error(9, ParseColorReason::Expected(')')));
+    assert_eq!(Color::parse("rgb(1 2 3"), error(9, ParseColorReason::Expected(')')));
+    assert_eq!(Color::parse("rgb(1x2 3)"), error(5, ParseColorReason::Expected(' ')));
+    assert_eq!(Color::parse("hwb(361 0% 0%)"), error(4, ParseColorReason::OutOfRange));
+    assert_eq!(Color::parse("hwb(0 0% 101%)"), error(9, ParseColorReason::OutOfRange));
+    assert_eq!(Color::parse("rgb(0 0 0 / 2)"), error(12, ParseColorReason::OutOfRange));
+}
+
+#[test]
+fn test_hsl_hwb_parse_round_trip() {
+    for hue in (0..=360).step_by(3) {
+        for first in (0..=100).step_by(5) {
+            for second in (0..=100).step_by(5) {
+                let hsl = Color::new_hsl(hue, first, second);
+                assert_eq!(Color::parse(&hsl.to_string()), Ok(hsl));
+                let hwb = Color::new_hwb(hue, first, second);
+                assert_eq!(Color::parse(&hwb.to_string()), Ok(hwb));
+            }
+        }
+    }
+}
+
+#[test]
+fn test_hsl_conversions() {
+    assert_eq!(Color::new_hsl(0, 100, 50).to_rgb(), Color::new_rgb(255, 0, 0));
+    assert_eq!(Color::new_hsl(120, 100, 25).to_rgb(), Color::new_rgb(0, 128, 0));
+    assert_eq!(Color::new_hsl(210, 50, 60).to_rgb(), Color::new_rgb(102, 153, 204));
+    assert_eq!(Color::new_hsl(360, 0, 100).to_rgb(), Color::new_rgb(255, 255, 255));
+
+    assert_eq!(Color::new_rgb(102, 153, 204).to_hsl(), Color::new_hsl(210, 50, 60));
+    assert_eq!(Color::new_rgb(1, 20, 123).to_hsl(), Color::new_hsl(231, 98, 24));
+    assert_eq!(Color::new_rgb(255, 255, 255).to_hsl(), Color::new_hsl(0, 0, 100));
+    assert_eq!(Color::new_rgb(0, 0, 0).to_hsl(), Color::new_hsl(0, 0, 0));
+
+    assert_eq!(Color::new_hsl(120, 100, 25).to_hsv(), Color::new_hsv(120, 100, 50));
+    assert_eq!(Color::new_hsv(120, 100, 50).to_hsl(), Color::new_hsl(120, 100, 25));
+    assert_eq!(Color::new_hsv(90, 3, 99).to_hsl(), Color::new_hsl(90, 60, 98));
+    assert_eq!(Color::new_hsva(0, 100, 100, 50).to_hsl(), Color::new_hsl(0, 100, 50));
+    assert_eq!(Color::new_hsl(210, 50, 60).to_hsl(), Color::new_hsl(210, 50, 60));
+}
+
+#[test]
+fn test_hwb_conversions() {
+    assert_eq!(Color::new_hwb(0, 0, 0).to_rgb(), Color::new_rgb(255, 0, 0));
+    assert_eq!(Color::new_hwb(120, 0, 50).to_rgb(), Color::new_rgb(0, 128, 0));
+    assert_eq!(Color::new_hwb(210, 40, 20).to_rgb(), Color::new_rgb(102, 153, 204));
+    assert_eq!(Color::new_hwb(0, 70, 70).to_rgb(), Color::new_rgb(128, 128, 128));
+    assert_eq!(Color::new_hwb(240, 100, 0).to_rgb(), Color::new_rgb(255, 255, 255));
+
+    assert_eq!(Color::new_rgb(102, 153, 204).to_hwb(), Color::new_hwb(210, 40, 20));
+    assert_eq!(Color::new_rgb(128, 128, 128).to_hwb(), Color::new_hwb(0, 50, 50));
+
+    assert_eq!(Color::new_hwb(120, 0, 50).to_hsv(), Color::new_hsv(120, 100, 50));
+    assert_eq!(Color::new_hsv(90, 3, 99).to_hwb(), Color::new_hwb(90, 96, 1));
+    assert_eq!(Color::new_hwb(0, 70, 70).to_hsv(), Color::new_hsv(0, 0, 50));
+    assert_eq!(Color::new_hwb(210, 40, 20).to_hsl(), Color::new_hsl(210, 50, 60));
+    assert_eq!(Color::new_hsl(210, 50, 60).to_hwb(), Color::new_hwb(210, 40, 20));
+    assert_eq!(Color::new_rgba(102, 153, 204, 0).to_hwb(), Color::new_hwb(210, 40, 20));
+}
+
+#[test]
+fn test_hsl_hwb_invert() {
+    assert_eq!(Color::new_hsl(120, 90, 35).invert(), Color::new_hsl(240, 10, 65));
+    assert_eq!(Color::new_hwb(0, 0, 100).invert(), Color::new_hwb(360, 100, 0));
+    assert_eq!(Color::new_hwb(90, 3, 9).invert().invert(), Color::new_hwb(90, 3, 9));
+}
+
+#[test]
+fn test_mix() {
+    let black = Color::new_rgb(0, 0, 0);
+    let white = Color::new_rgb(255, 255, 255);
+    assert_eq!(black.mix(&white, 0.0), black);
+    assert_eq!(black.mix(&white, 1.0), white);
+    assert_eq!(black.mix(&white, 0.5), Color::new_rgb(128, 128, 128));
+    assert_eq!(black.mix(&white, -1.0), black);
+    assert_eq!(black.mix(&white, 2.0), white);
+    assert_eq!(black.mix(&white, f64::NAN), black);
+
+    let red = Color::new_hsv(0, 100, 100);
+    let blue = Color::new_hsl(240, 100, 50);
+    assert_eq!(red.mix(&blue, 0.0), Color::new_rgb(255, 0, 0));
+    assert_eq!(red.mix(&blue, 0.25), Color::new_rgb(191, 0, 64));
+
+    let clear = Color::new_rgba(0, 0, 0, 0);
+    assert_eq!(clear.mix(&white, 0.5), Color::new_rgba(128, 128, 128, 128));
+    assert_eq!(clear.mix(&white, 1.0), white);
+    assert_eq!(Color::new_hsva(0, 0, 100, 0).mix(&white, 0.0), Color::new_rgba(255, 255, 255, 0));
+}
+
+#[test]
+fn test_blend_modes_opaque() {
+    let orange = Color::new_rgb(255, 128, 0);
+    let teal = Color::new_hsv(200, 75, 80);
+
+    let expected = [
+        (BlendMode::Normal, "#ff8000", "#3399cc"),
+        (BlendMode::Multiply, "#334d00", "#334d00"),
+        (BlendMode::Screen, "#ffcccc", "#ffcccc"),
+        (BlendMode::Overlay, "#669999", "#ff9900"),
+        (BlendMode::Darken, "#338000", "#338000"),
+        (BlendMode::Lighten, "#ff99cc", "#ff99cc"),
+        (BlendMode::Difference, "#cc19cc", "#cc19cc"),
+    ];
+    for (mode, orange_on_teal, teal_on_orange) in expected {
+        assert_eq!(orange.blend(&teal, mode).to_string(), orange_on_teal, "{:?}", mode);
+        assert_eq!(teal.blend(&orange, mode).to_string(), teal_on_orange, "{:?}", mode);
+    }
+}
+
+#[test]
+fn test_over() {
+    let white = Color::new_rgb(255, 255, 255);
+    let glass = Color::new_rgba(255, 0, 0, 128);
+
+    assert_eq!(Color::new_rgb(1, 20, 123).over(&glass), Color::new_rgb(1, 20, 123));
+    assert_eq!(Color::new_hsv(0, 100, 100).over(&white), Color::new_rgb(255, 0, 0));
+    assert_eq!(glass.over(&white), Color::new_rgb(255, 127, 127));
+    assert_eq!(glass.over(&Color::new_rgba(0, 0, 255, 128)), Color::new_rgba(170, 0, 85, 192));
+    assert_eq!(Color::new_rgba(0, 0, 0, 0).over(&glass), glass);
+    assert_eq!(Color::new_rgba(9, 9, 9, 0).over(&Color::new_rgba(1, 2, 3, 0)), Color::new_rgba(0, 0, 0, 0));
+
+    assert_eq!(glass.blend(&white, BlendMode::Multiply), Color::new_rgb(255, 127, 127));
+    assert_eq!(
+        glass.blend(&Color::new_rgba(0, 0, 255, 128), BlendMode::Screen),
+        Color::new_rgba(170, 0, 170, 192)
+    );
+}
+
+#[test]
+fn test_color_operators() {
+    let glass = Color::new_rgba(255, 0, 0, 128);
+    let blue_glass = Color::new_rgba(0, 0, 255, 128);
+
+    assert_eq!(glass * blue_glass, Color::new_rgba(85, 0, 85, 192));
+    assert_eq!(blue_glass * glass, Color::new_rgba(85, 0, 85, 192));
+    assert_eq!(Color::new_rgb(128, 128, 128) * Color::new_hsv(0, 100, 100), Color::new_rgb(128, 0, 0));
+
+    assert_eq!(!Color::new_rgb(0, 255, 0), Color::new_rgb(255, 0, 255));
+    assert_eq!(!Color::new_hsv(120, 90, 35), Color::new_hsv(240, 10, 65));
+    assert_eq!(!glass, Color::new_rgba(0, 255, 255, 128));
+}
+
+fn assert_close(left: f64, right: f64, epsilon: f64) {
+    assert!((left - right).abs() <= epsilon, "{} vs {}", left, right);
+}
+
+#[test]
+fn test_relative_luminance() {
+    assert_close(Color::new_rgb(0, 0, 0).relative_luminance(), 0.0, 1e-9);
+    assert_close(Color::new_rgb(255, 255, 255).relative_luminance(), 1.0, 1e-9);
+    assert_close(Color::new_rgb(255, 0, 0).relative_luminance(), 0.2126, 1e-9);
+    assert_close(Color::new_rgb(0, 255, 0).relative_luminance(), 0.7152, 1e-9);
+    assert_close(Color::new_rgb(0, 0, 255).relative_luminance(), 0.0722, 1e-9);
+    assert_close(Color::new_rgb(10, 10, 10).relative_luminance(), 10.0 / 255.0 / 12.92, 1e-9);
+    assert_close(Color::new_rgb(0x77, 0x77, 0x77).relative_luminance(), 0.18447, 1e-5);
+
+    assert_close(Color::new_hsv(120, 100, 100).relative_luminance(), 0.7152, 1e-9);
+    assert_close(Color::new_hsl(0, 0, 100).relative_luminance(), 1.0, 1e-9);
+    assert_close(Color::new_rgba(255, 0, 0, 0).relative_luminance(), 0.2126, 1e-9);
+}
+
+#[test]
+fn test_contrast_ratio() {
+    let black = Color::new_rgb(0, 0, 0);
+    let white = Color::new_hsv(0, 0, 100);
+
+    assert_close(black.contrast_ratio(&white), 21.0, 1e-9);
+    assert_close(white.contrast_ratio(&black), 21.0, 1e-9);
+    assert_close(white.contrast_ratio(&white), 1.0, 1e-9);
+    assert_close(Color::new_rgb(0x77, 0x77, 0x77).contrast_ratio(&white), 4.478, 1e-3);
+    assert_close(Color::new_rgb(0x76, 0x76, 0x76).contrast_ratio(&white), 4.542, 1e-3);
+    assert_close(Color::new_rgb(255, 0, 0).contrast_ratio(&Color::new_rgb(0, 0, 255)), 2.149, 1e-3);
+}
+
+#[test]
+fn test_passes_contrast() {
+    let white = Color::new_rgb(255, 255, 255);
+    let gray77 = Color::new_rgb(0x77, 0x77, 0x77);
+    let gray76 = Color::new_rgb(0x76, 0x76, 0x76);
+
+    assert!(!gray77.passes_contrast(&white, WcagLevel::AA, TextSize::Normal));
+    assert!(gray77.passes_contrast(&white, WcagLevel::AA, TextSize::Large));
+    assert!(gray76.passes_contrast(&white, WcagLevel::AA, TextSize::Normal));
+    assert!(gray76.passes_contrast(&white, WcagLevel::AAA, TextSize::Large));
+    assert!(!gray76.passes_contrast(&white, WcagLevel::AAA, TextSize::Normal));
+    assert!(white.passes_contrast(&gray76, WcagLevel::AA, TextSize::Normal));
+
+    let dark = Color::new_rgb(0x59, 0x59, 0x59);
+    assert!(dark.passes_contrast(&white, WcagLevel::AAA, TextSize::Normal));
+    assert!(!white.passes_contrast(&white, WcagLevel::AA, TextSize::Large));
+}
+
+#[test]
+fn test_best_text_color() {
+    let black = Color::new_rgb(0, 0, 0);
+    let white = Color::new_rgb(255, 255, 255);
+
+    assert_eq!(Color::best_text_color(&white), black);
+    assert_eq!(Color::best_text_color(&black), white);
+    assert_eq!(Color::best_text_color(&Color::new_hsv(60, 100, 100)), black);
+    assert_eq!(Color::best_text_color(&Color::new_rgb(0, 0, 255)), white);
+    assert_eq!(Color::best_text_color(&Color::new_rgb(0x75, 0x75, 0x75)), white);
+    assert_eq!(Color::best_text_color(&Color::new_rgb(0x76, 0x76, 0x76)), black);
+    assert_eq!(Color::best_text_color(&Color::new_hsl(0, 100, 50)), black);
+}
+
+#[test]
+fn test_from_name() {
+    assert_eq!(Color::from_name("black"), Some(Color::new_rgb(0, 0, 0)));
+    assert_eq!(Color::from_name("white"), Some(Color::new_rgb(255, 255, 255)));
+    assert_eq!(Color::from_name("rebeccapurple"), Some(Color::new_rgb(102, 51, 153)));
+    assert_eq!(Color::from_name("aliceblue"), Some(Color::new_rgb(240, 248, 255)));
+    assert_eq!(Color::from_name("yellowgreen"), Some(Color::new_rgb(154, 205, 50)));
+    assert_eq!(Color::from_name("darkgoldenrod"), Some(Color::new_rgb(184, 134, 11)));
+    assert_eq!(Color::from_name("lightgoldenrodyellow"), Some(Color::new_rgb(250, 250, 210)));
+    assert_eq!(Color::from_name("gray"), Some(Color::new_rgb(128, 128, 128)));
+    assert_eq!(Color::from_name("grey"), Some(Color::new_rgb(128, 128, 128)));
+    assert_eq!(Color::from_name("darkslategrey"), Some(Color::new_rgb(47, 79, 79)));
+    assert_eq!(Color::from_name("transparent"), Some(Color::new_rgba(0, 0, 0, 0)));
+
+    assert_eq!(Color::from_name("RebeccaPurple"), Some(Color::new_rgb(102, 51, 153)));
+    assert_eq!(Color::from_name("TRANSPARENT"), Some(Color::new_rgba(0, 0, 0, 0)));
+
+    assert_eq!(Color::from_name(""), None);
+    assert_eq!(Color::from_name("reddish"), None);
+    assert_eq!(Color::from_name(" red"), None);
+    assert_eq!(Color::from_name("#ff0000"), None);
+}
+
+#[test]
+fn test_name() {
+    assert_eq!(Color::new_rgb(102, 51, 153).name(), Some("rebeccapurple"));
+    assert_eq!(Color::new_rgb(255, 0, 0).name(), Some("red"));
+    assert_eq!(Color::new_rgb(0, 255, 255).name(), Some("aqua"));
+    assert_eq!(Color::new_rgb(255, 0, 255).name(), Some("fuchsia"));
+    assert_eq!(Color::new_rgb(128, 128, 128).name(), Some("gray"));
+    assert_eq!(Color::new_rgb(211, 211, 211).name(), Some("lightgray"));
+    assert_eq!(Color::new_rgb(1, 20, 123).name(), None);
+
+    assert_eq!(Color::new_hsv(0, 100, 100).name(), Some("red"));
+    assert_eq!(Color::new_hsl(120, 100, 25).name(), Some("green"));
+    assert_eq!(Color::new_hwb(0, 100, 0).name(), Some("white"));
+    assert_eq!(Color::new_rgba(255, 0, 0, 255).name(), Some("red"));
+    assert_eq!(Color::new_hsva(0, 100, 100, 100).name(), Some("red"));
+    assert_eq!(Color::new_rgba(255, 0, 0, 254).name(), None);
+    assert_eq!(Color::new_rgba(0, 0, 0, 0).name(), Some("transparent"));
+    assert_eq!(Color::new_rgba(255, 255, 255, 0).name(), None);
+    assert_eq!(Color::new_hsva(0, 0, 0, 0).name(), Some("transparent"));
+}
+
+#[test]
+fn test_name_round_trip() {
+    let names = [
+        "aliceblue", "aqua", "blueviolet", "chartreuse", "cornflowerblue", "darkorange", "fuchsia",
+        "gold", "gray", "hotpink", "indigo", "lavenderblush", "lime", "mediumseagreen", "navy",
+        "olivedrab", "papayawhip", "rebeccapurple", "salmon", "steelblue", "tomato", "wheat",
+    ];
+    for name in names {
+        assert_eq!(Color::from_name(name).unwrap().name(), Some(name));
+    }
+}
+
+#[test]
+fn test_to_string_prefer_name() {
+    let named = ColorFormat { prefer_name: true, ..ColorFormat::default() };
+    let plain = ColorFormat::default();
+
+    assert_eq!(Color::new_rgb(102, 51, 153).to_string_with(&named), "rebeccapurple");
+    assert_eq!(Color::new_rgb(102, 51, 153).to_string_with(&plain), "#663399");
+    assert_eq!(Color::new_rgb(102, 51, 154).to_string_with(&named), "#66339a");
+    assert_eq!(Color::new_hsv(0, 100, 100).to_string_with(&named), "red");
+    assert_eq!(Color::new_hsv(1, 100, 100).to_string_with(&named), "hsv(1,100%,100%)");
+    assert_eq!(Color::new_hsv(0, 100, 100).to_string_with(&plain), "hsv(0,100%,100%)");
+    assert_eq!(Color::new_rgba(0, 0, 0, 0).to_string_with(&named), "transparent");
+    assert_eq!(Color::new_rgba(0, 0, 0, 1).to_string_with(&named), "#00000001");
+}
+
+#[test]
+fn test_display() {
+    assert_eq!(format!("{}", Color::new_rgb(1, 20, 123)), "#01147b");
+    assert_eq!(format!("{}", Color::new_hsv(90, 3, 99)), "hsv(90,3%,99%)");
+    assert_eq!(format!("{}", Color::new_rgba(255, 1, 255, 128)), "#ff01ff80");
+    assert_eq!(format!("{}", Color::new_hsva(90, 3, 99, 50)), "hsva(90,3%,99%,50%)");
+    assert_eq!(format!("{}", Color::new_hsl(120, 100, 50)), "hsl(120 100% 50%)");
+    assert_eq!(format!("{}", Color::new_hwb(90, 3, 9)), "hwb(90 3% 9%)");
+
+    assert_eq!(format!("{:>9}|", Color::new_rgb(0, 0, 0)), "  #000000|");
+    assert_eq!(format!("{:<9}|", Color::new_rgb(0, 0, 0)), "#000000  |");
+    assert_eq!(format!("{:*^16}", Color::new_hsv(0, 0, 0)), "**hsv(0,0%,0%)**");
+}
+
+#[test]
+fn test_hex_formatting() {
+    let color = Color::new_rgb(255, 1, 171);
+    assert_eq!(format!("{:x}", color), "ff01ab");
+    assert_eq!(format!("{:X}", color), "FF01AB");
+    assert_eq!(format!("{:#x}", color), "#ff01ab");
+    assert_eq!(format!("{:#X}", color), "#FF01AB");
+    assert_eq!(format!("{:>8x}", color), "  ff01ab");
+
+    assert_eq!(format!("{:#x}", Color::new_hsv(0, 100, 100)), "#ff0000");
+    assert_eq!(format!("{:#x}", Color::new_hsl(120, 100, 25)), "#008000");
+    assert_eq!(format!("{:x}", Color::new_rgba(255, 1, 171, 10)), "ff01ab0a");
+    assert_eq!(format!("{:#X}", Color::new_hsva(0, 100, 100, 50)), "#FF000080");
+}
+
+#[test]
+fn test_format_hex_options() {
+    let format = |short_hex, uppercase_hex, alpha_first| ColorFormat {
+        short_hex,
+        uppercase_hex,
+        alpha_first,
+        ..ColorFormat::default()
+    };
+
+    let opaque = Color::new_rgb(255, 0, 170);
+    assert_eq!(opaque.to_string_with(&format(false, false, false)), "#ff00aa");
+    assert_eq!(opaque.to_string_with(&format(true, false, false)), "#f0a");
+    assert_eq!(opaque.to_string_with(&format(true, true, false)), "#F0A");
+    assert_eq!(opaque.to_string_with(&format(false, true, true)), "#FF00AA");
+    assert_eq!(Color::new_rgb(255, 1, 170).to_string_with(&format(true, false, false)), "#ff01aa");
+
+    let transparent = Color::new_rgba(255, 0, 170, 136);
+    assert_eq!(transparent.to_string_with(&format(true, false, false)), "#f0a8");
+    assert_eq!(transparent.to_string_with(&format(false, true, true)), "#88FF00AA");
+    assert_eq!(transparent.to_string_with(&format(true, false, true)), "#8f0a");
+    assert_eq!(Color::new_rgba(255, 0, 170, 137).to_string_with(&format(true, false, false)), "#ff00aa89");
+}
+
+#[test]
+fn test_format_models() {
+    let model = |model| ColorFormat { model: Some(model), ..ColorFormat::default() };
+    let color = Color::new_rgb(102, 153, 204);
+
+    assert_eq!(color.to_string_with(&model(ColorModel::Hex)), "#6699cc");
+    assert_eq!(color.to_string_with(&model(ColorModel::Rgb)), "rgb(102,153,204)");
+    assert_eq!(color.to_string_with(&model(ColorModel::Hsv)), "hsv(210,50%,80%)");
+    assert_eq!(color.to_string_with(&model(ColorModel::Hsl)), "hsl(210 50% 60%)");
+    assert_eq!(color.to_string_with(&model(ColorModel::Hwb)), "hwb(210 40% 20%)");
+
+    let hsv = Color::new_hsv(210, 50, 80);
+    assert_eq!(hsv.to_string_with(&model(ColorModel::Hex)), "#6699cc");
+    assert_eq!(hsv.to_string_with(&model(ColorModel::Hsv)), "hsv(210,50%,80%)");
+
+    let transparent = Color::new_rgba(102, 153, 204, 128);
+    assert_eq!(transparent.to_string_with(&model(ColorModel::Rgb)), "rgba(102,153,204,0.502)");
+    assert_eq!(transparent.to_string_with(&model(ColorModel::Hsv)), "hsva(210,50%,80%,50%)");
+    assert_eq!(transparent.to_string_with(&model(ColorModel::Hsl)), "hsl(210 50% 60% / 50%)");
+    assert_eq!(transparent.to_string_with(&model(ColorModel::Hwb)), "hwb(210 40% 20% / 50%)");
+
+    let transparent = Color::new_hsva(210, 50, 80, 25);
+    assert_eq!(transparent.to_string_with(&model(ColorModel::Hex)), "#6699cc40");
+    assert_eq!(transparent.to_string_with(&model(ColorModel::Rgb)), "rgba(102,153,204,0.251)");
+    assert_eq!(transparent.to_string_with(&model(ColorModel::Hsl)), "hsl(210 50% 60% / 25%)");
+
+    assert_eq!(Color::new_rgba(0, 0, 0, 255).to_string_with(&model(ColorModel::Rgb)), "rgba(0,0,0,1)");
+    assert_eq!(Color::new_rgba(0, 0, 0, 0).to_string_with(&model(ColorModel::Rgb)), "rgba(0,0,0,0)");
+    assert_eq!(Color::new_rgba(0, 0, 0, 51).to_string_with(&model(ColorModel::Rgb)), "rgba(0,0,0,0.2)");
+}
+
+#[test]
+fn test_format_syntax_options() {
+    let format = |model, modern_syntax, space_after_comma| ColorFormat {
+        model: Some(model),
+        modern_syntax,
+        space_after_comma,
+        ..ColorFormat::default()
+    };
+    let opaque = Color::new_rgb(1, 20, 123);
+    let transparent = Color::new_hsva(90, 3, 99, 50);
+
+    assert_eq!(opaque.to_string_with(&format(ColorModel::Rgb, false, true)), "rgb(1, 20, 123)");
+    assert_eq!(opaque.to_string_with(&format(ColorModel::Rgb, true, false)), "rgb(1 20 123)");
+    assert_eq!(opaque.to_string_with(&format(ColorModel::Rgb, true, true)), "rgb(1 20 123)");
+    assert_eq!(opaque.to_string_with(&format(ColorModel::Hex, true, true)), "#01147b");
+
+    assert_eq!(transparent.to_string_with(&format(ColorModel::Hsv, false, true)), "hsva(90, 3%, 99%, 50%)");
+    assert_eq!(transparent.to_string_with(&format(ColorModel::Hsv, true, false)), "hsv(90 3% 99% / 50%)");
+    assert_eq!(transparent.to_string_with(&format(ColorModel::Rgb, true, false)), "rgb(249 252 245 / 0.502)");
+    assert_eq!(transparent.to_string_with(&format(ColorModel::Hsl, false, true)), "hsl(90 60% 98% / 50%)");
+
+    let named = ColorFormat { prefer_name: true, ..format(ColorModel::Rgb, true, true) };
+    assert_eq!(Color::new_hsv(0, 100, 100).to_string_with(&named), "red");
+    assert_eq!(Color::new_hsv(1, 100, 100).to_string_with(&named), "rgb(255 4 0)");
+}
+
+#[test]
+fn test_format_parse_round_trip() {
+    let formats = [
+        ColorFormat { short_hex: true, uppercase_hex: true, ..ColorFormat::default() },
+        ColorFormat { model: Some(ColorModel::Rgb), ..ColorFormat::default() },
+        ColorFormat { model: Some(ColorModel::Rgb), modern_syntax: true, ..ColorFormat::default() },
+        ColorFormat { model: Some(ColorModel::Rgb), space_after_comma: true, ..ColorFormat::default() },
+    ];
+    for alpha in 0..=255 {
+        for value in [0, 17, 100, 255] {
+            let opaque = Color::new_rgb(value, alpha, 255 - value);
+            let transparent = Color::new_rgba(value, 255 - value, value, alpha);
+            for format in &formats {
+                assert_eq!(Color::parse(&opaque.to_string_with(format)), Ok(opaque), "{:?}", format);
+                assert_eq!(Color::parse(&transparent.to_string_with(format)), Ok(transparent), "{:?}", format);
+            }
+        }
+    }
+
+    let formats = [
+        ColorFormat { modern_syntax: true, ..ColorFormat::default() },
+        ColorFormat { space_after_comma: true, ..ColorFormat::default() },
+    ];
+    for alpha in 0..=100 {
+        let transparent = Color::new_hsva(alpha as u16 * 3, alpha, 100 - alpha, alpha);
+        for format in &formats {
+            assert_eq!(Color::parse(&transparent.to_string_with(format)), Ok(transparent), "{:?}", format);
+        }
+    }
+}
+
+macro_rules! assert_components {
+    ($value:expr, $a:ident: $x:expr, $b:ident: $y:expr, $c:ident: $z:expr, $epsilon:expr) => {{
+        let value = $value;
+        assert_close(value.$a, $x, $epsilon);
+        assert_close(value.$b, $y, $epsilon);
+        assert_close(value.$c, $z, $epsilon);
+    }};
+}
+
+#[test]
+fn test_to_xyz_and_lab() {
+    let red = Color::new_rgb(255, 0, 0);
+    let blue = Color::new_hsv(240, 100, 100);
+    let white = Color::new_rgb(255, 255, 255);
+
+    assert_components!(red.to_xyz(), x: 0.4124564, y: 0.2126729, z: 0.0193339, 1e-7);
+    assert_components!(white.to_xyz(), x: 0.95047, y: 1.0, z: 1.08883, 1e-6);
+    assert_components!(Color::new_rgb(128, 128, 128).to_xyz(), x: 0.205169, y: 0.215861, z: 0.235035, 1e-6);
+
+    assert_components!(red.to_lab(), l: 53.2408, a: 80.0925, b: 67.2032, 1e-3);
+    assert_components!(blue.to_lab(), l: 32.2970, a: 79.1875, b: -107.8602, 1e-3);
+    assert_components!(white.to_lab(), l: 100.0, a: 0.0, b: 0.0, 1e-3);
+    assert_components!(Color::new_rgb(0, 0, 0).to_lab(), l: 0.0, a: 0.0, b: 0.0, 1e-9);
+    assert_components!(Color::new_rgb(1, 1, 1).to_lab(), l: 0.2742, a: 0.0, b: 0.0, 1e-3);
+    assert_components!(Color::new_rgb(102, 51, 153).to_lab(), l: 32.9025, a: 42.8831, b: -47.1486, 1e-3);
+
+    assert_components!(red.to_lch(), l: 53.2408, c: 104.5518, h: 39.999, 1e-3);
+    assert_components!(blue.to_lch(), l: 32.2970, c: 133.8076, h: 306.2849, 1e-3);
+    assert_components!(white.to_lch(), l: 100.0, c: 0.0, h: 0.0, 1e-3);
+    assert_components!(Color::new_rgb(128, 128, 128).to_lch(), l: 53.5850, c: 0.0, h: 0.0, 1e-3);
+}
+
+#[test]
+fn test_to_oklab() {
+    let red = Color::new_rgb(255, 0, 0);
+    let blue = Color::new_hsl(240, 100, 50);
+
+    assert_components!(red.to_oklab(), l: 0.627955, a: 0.224863, b: 0.125846, 1e-5);
+    assert_components!(blue.to_oklab(), l: 0.452014, a: -0.032457, b: -0.311528, 1e-5);
+    assert_components!(Color::new_rgb(255, 255, 255).to_oklab(), l: 1.0, a: 0.0, b: 0.0, 1e-5);
+    assert_components!(Color::new_rgb(128, 128, 128).to_oklab(), l: 0.599871, a: 0.0, b: 0.0, 1e-5);
+
+    assert_components!(red.to_oklch(), l: 0.627955, c: 0.257683, h: 29.2339, 1e-4);
+    assert_components!(blue.to_oklch(), l: 0.452014, c: 0.313214, h: 264.0520, 1e-4);
+    assert_components!(Color::new_rgb(255, 255, 255).to_oklch(), l: 1.0, c: 0.0, h: 0.0, 1e-5);
+}
+
+#[test]
+fn test_perceptual_from_impls() {
+    let color = Color::new_rgb(102, 51, 153);
+    let xyz = Xyz::from(color);
+
+    let lab = color.to_lab();
+    assert_components!(Lab::from(xyz), l: lab.l, a: lab.a, b: lab.b, 1e-6);
+    let lch = color.to_lch();
+    assert_components!(Lch::from(Lab::from(xyz)), l: lch.l, c: lch.c, h: lch.h, 1e-6);
+    let oklch = color.to_oklch();
+    assert_components!(Oklch::from(Oklab::from(xyz)), l: oklch.l, c: oklch.c, h: oklch.h, 1e-6);
+    assert_eq!(Lch::from(color), color.to_lch());
+    assert_eq!(Oklab::from(color), color.to_oklab());
+
+    let back: Xyz = color.to_lab().into();
+    assert_components!(back, x: xyz.x, y: xyz.y, z: xyz.z, 1e-6);
+    let back: Xyz = color.to_oklch().into();
+    assert_components!(back, x: xyz.x, y: xyz.y, z: xyz.z, 1e-6);
+    let lab: Lab = color.to_lch().into();
+    assert_components!(lab, l: color.to_lab().l, a: color.to_lab().a, b: color.to_lab().b, 1e-9);
+    let oklab: Oklab = Oklch::from(color).into();
+    assert_components!(oklab, l: color.to_oklab().l, a: color.to_oklab().a, b: color.to_oklab().b, 1e-6);
+}
+
+#[test]
+fn test_perceptual_round_trip() {
+    for mapping in [GamutMapping::Clip, GamutMapping::ReduceChroma] {
+        for red in (0..=255).step_by(15) {
+            for green in (0..=255).step_by(15) {
+                for blue in (0..=255).step_by(15) {
+                    let color = Color::new_rgb(red, green, blue);
+                    assert_eq!(color.to_xyz().to_color(mapping), color);
+                    assert_eq!(color.to_lab().to_color(mapping), color);
+                    assert_eq!(color.to_lch().to_color(mapping), color);
+                    assert_eq!(color.to_oklab().to_color(mapping), color);
+                    assert_eq!(color.to_oklch().to_color(mapping), color);
+                }
+            }
+        }
+    }
+
+    assert_eq!(Color::new_hsv(90, 3, 99).to_lab().to_color(GamutMapping::Clip), Color::new_rgb(249, 252, 245));
+    assert_eq!(Color::new_rgba(1, 20, 123, 0).to_oklab().to_color(GamutMapping::Clip), Color::new_rgb(1, 20, 123));
+}
+
+#[test]
+fn test_gamut_mapping() {
+    assert!(Color::new_rgb(0, 255, 0).to_oklch().in_gamut());
+    assert!(Lab { l: 50.0, a: 0.0, b: 0.0 }.in_gamut());
+    assert!(!Lab { l: 50.0, a: 0.0, b: 100.0 }.in_gamut());
+    assert!(!Xyz { x: 0.0, y: 1.0, z: 0.0 }.in_gamut());
+
+    let green = Oklch { l: 0.7, c: 0.4, h: 150.0 };
+    assert!(!green.in_gamut());
+    assert_eq!(green.to_color(GamutMapping::Clip), Color::new_rgb(0, 214, 0));
+
+    let mapped = green.to_color(GamutMapping::ReduceChroma);
+    assert_rgb_close(mapped.unwrap_rgb(), (0, 190, 88), 1);
+    let mapped = mapped.to_oklch();
+    assert_close(mapped.l, 0.7, 0.005);
+    assert_close(mapped.h, 150.0, 1.0);
+    assert!(mapped.c < 0.2);
+
+    let yellow = Lch { l: 90.0, c: 120.0, h: 100.0 };
+    let mapped = yellow.to_color(GamutMapping::ReduceChroma).to_oklch();
+    let expected = Oklch::from(Xyz::from(yellow));
+    assert_close(mapped.l, expected.l, 0.005);
+    assert_close(mapped.h, expected.h, 1.0);
+
+    assert_eq!(Oklch { l: 1.2, c: 0.3, h: 0.0 }.to_color(GamutMapping::ReduceChroma), Color::new_rgb(255, 255, 255));
+    assert_eq!(Oklch { l: -0.1, c: 0.3, h: 0.0 }.to_color(GamutMapping::ReduceChroma), Color::new_rgb(0, 0, 0));
+    assert_eq!(Oklab { l: 1.2, a: 0.0, b: 0.0 }.to_color(GamutMapping::Clip), Color::new_rgb(255, 255, 255));
+}
+
+#[test]
+fn test_ciede2000_reference_pairs() {
+    // Sharma, Wu, Dalal: "The CIEDE2000 Color-Difference Formula", table 1
+    let pairs = [
+        ((50.0000, 2.6772, -79.7751), (50.0000, 0.0000, -82.7485), 2.0425),
+        ((50.0000, 3.1571, -77.2803), (50.0000, 0.0000, -82.7485), 2.8615),
+        ((50.0000, 2.8361, -74.0200), (50.0000, 0.0000, -82.7485), 3.4412),
+        ((50.0000, -1.3802, -84.2814), (50.0000, 0.0000, -82.7485), 1.0000),
+        ((50.0000, -1.1848, -84.8006), (50.0000, 0.0000, -82.7485), 1.0000),
+        ((50.0000, -0.9009, -85.5211), (50.0000, 0.0000, -82.7485), 1.0000),
+        ((50.0000, 0.0000, 0.0000), (50.0000, -1.0000, 2.0000), 2.3669),
+        ((50.0000, -1.0000, 2.0000), (50.0000, 0.0000, 0.0000), 2.3669),
+        ((50.0000, 2.4900, -0.0010), (50.0000, -2.4900, 0.0009), 7.1792),
+        ((50.0000, 2.4900, -0.0010), (50.0000, -2.4900, 0.0010), 7.1792),
+        ((50.0000, 2.4900, -0.0010), (50.0000, -2.4900, 0.0011), 7.2195),
+        ((50.0000, 2.4900, -0.0010), (50.0000, -2.4900, 0.0012), 7.2195),
+        ((50.0000, -0.0010, 2.4900), (50.0000, 0.0009, -2.4900), 4.8045),
+        ((50.0000, -0.0010, 2.4900), (50.0000, 0.0010, -2.4900), 4.8045),
+        ((50.0000, -0.0010, 2.4900), (50.0000, 0.0011, -2.4900), 4.7461),
+        ((50.0000, 2.5000, 0.0000), (50.0000, 0.0000, -2.5000), 4.3065),
+        ((50.0000, 2.5000, 0.0000), (73.0000, 25.0000, -18.0000), 27.1492),
+        ((50.0000, 2.5000, 0.0000), (61.0000, -5.0000, 29.0000), 22.8977),
+        ((50.0000, 2.5000, 0.0000), (56.0000, -27.0000, -3.0000), 31.9030),
+        ((50.0000, 2.5000, 0.0000), (58.0000, 24.0000, 15.0000), 19.4535),
+        ((50.0000, 2.5000, 0.0000), (50.0000, 3.1736, 0.5854), 1.0000),
+        ((50.0000, 2.5000, 0.0000), (50.0000, 3.2972, 0.0000), 1.0000),
+        ((50.0000, 2.5000, 0.0000), (50.0000, 1.8634, 0.5757), 1.0000),
+        ((50.0000, 2.5000, 0.0000), (50.0000, 3.2592, 0.3350), 1.0000),
+        ((60.2574, -34.0099, 36.2677), (60.4626, -34.1751, 39.4387), 1.2644),
+        ((63.0109, -31.0961, -5.8663), (62.8187, -29.7946, -4.0864), 1.2630),
+        ((61.2901, 3.7196, -5.3901), (61.4292, 2.2480, -4.9620), 1.8731),
+        ((35.0831, -44.1164, 3.7933), (35.0232, -40.0716, 1.5901), 1.8645),
+        ((22.7233, 20.0904, -46.6940), (23.0331, 14.9730, -42.5619), 2.0373),
+        ((36.4612, 47.8580, 18.3852), (36.2715, 50.5065, 21.2231), 1.4146),
+        ((90.8027, -2.0831, 1.4410), (91.1528, -1.6435, 0.0447), 1.4441),
+        ((90.9257, -0.5406, -0.9208), (88.6381, -0.8985, -0.7239), 1.5381),
+        ((6.7747, -0.2908, -2.4247), (5.8714, -0.0985, -2.2286), 0.6377),
+        ((2.0776, 0.0795, -1.1350), (0.9033, -0.0636, -0.5514), 0.9082),
+    ];
+
+    for ((l1, a1, b1), (l2, a2, b2), expected) in pairs {
+        let first = Lab { l: l1, a: a1, b: b1 };
+        let second = Lab { l: l2, a: a2, b: b2 };
+
+        assert_close(first.delta_e(&second, Metric::Ciede2000), expected, 1e-4);
+        assert_close(second.delta_e(&first, Metric::Ciede2000), expected, 1e-4);
+    }
+}
+
+#[test]
+fn test_cie76_and_cie94() {
+    let first = Lab { l: 50.0, a: 2.6772, b: -79.7751 };
+    let second = Lab { l: 50.0, a: 0.0, b: -82.7485 };
+
+    assert_close(first.delta_e(&second, Metric::Cie76), 4.0011, 1e-4);
+    assert_close(second.delta_e(&first, Metric::Cie76), 4.0011, 1e-4);
+    assert_close(first.delta_e(&second, Metric::Cie94), 1.3950, 1e-4);
+    assert_close(second.delta_e(&first, Metric::Cie94), 1.3653, 1e-4);
+
+    let first = Lab { l: 60.2574, a: -34.0099, b: 36.2677 };
+    let second = Lab { l: 60.4626, a: -34.1751, b: 39.4387 };
+    assert_close(first.delta_e(&second, Metric::Cie94), 1.3910, 1e-4);
+
+    let gray = Lab { l: 50.0, a: 0.0, b: 0.0 };
+    let black = Lab { l: 0.0, a: 0.0, b: 0.0 };
+    for metric in [Metric::Cie76, Metric::Cie94, Metric::Ciede2000] {
+        assert_close(gray.delta_e(&gray, metric), 0.0, 1e-12);
+    }
+    assert_close(gray.delta_e(&black, Metric::Cie76), 50.0, 1e-12);
+    assert_close(gray.delta_e(&black, Metric::Cie94), 50.0, 1e-12);
+}
+
+#[test]
+fn test_color_delta_e() {
+    let red = Color::new_rgb(255, 0, 0);
+    let blue = Color::new_rgb(0, 0, 255);
+
+    assert_close(red.delta_e(&red, Metric::Ciede2000), 0.0, 1e-12);
+    assert_close(red.delta_e(&Color::new_hsv(0, 100, 100), Metric::Ciede2000), 0.0, 1e-12);
+    assert_close(red.delta_e(&Color::new_rgba(255, 0, 0, 0), Metric::Cie76), 0.0, 1e-12);
+    assert_close(red.delta_e(&blue, Metric::Cie76), red.to_lab().delta_e(&blue.to_lab(), Metric::Cie76), 1e-12);
+    assert_close(red.delta_e(&blue, Metric::Cie76), 176.3140, 1e-3);
+    assert_close(red.delta_e(&blue, Metric::Ciede2000), 52.8814, 1e-3);
+
+    let blue = Color::parse("#0a74da").unwrap();
+    assert!(blue.delta_e(&Color::parse("#0b74d9").unwrap(), Metric::Ciede2000) < 1.0);
+    assert!(blue.delta_e(&Color::parse("#0a75db").unwrap(), Metric::Ciede2000) < 1.0);
+    assert!(blue.delta_e(&Color::parse("#1a84ea").unwrap(), Metric::Ciede2000) > 2.0);
+}
+
+#[test]
+fn test_nearest() {
+    let palette = [
+        Color::new_rgb(0, 0, 0),
+        Color::new_rgb(255, 255, 255),
+        Color::new_rgb(255, 0, 0),
+        Color::new_rgb(0, 128, 0),
+        Color::new_rgb(0, 0, 255),
+    ];
+
+    assert_eq!(Color::new_rgb(250, 10, 20).nearest(&palette), Some(Color::new_rgb(255, 0, 0)));
+    assert_eq!(Color::new_rgb(20, 20, 20).nearest(&palette), Some(Color::new_rgb(0, 0, 0)));
+    assert_eq!(Color::new_hsv(120, 100, 60).nearest(&palette), Some(Color::new_rgb(0, 128, 0)));
+    assert_eq!(Color::new_rgb(240, 240, 250).nearest(&palette), Some(Color::new_rgb(255, 255, 255)));
+    assert_eq!(Color::new_rgb(0, 0, 255).nearest(&palette), Some(Color::new_rgb(0, 0, 255)));
+    assert_eq!(Color::new_rgb(0, 0, 255).nearest(&[]), None);
+
+    let duplicates = [Color::new_rgb(255, 0, 0), Color::new_hsv(0, 100, 100), Color::new_rgb(255, 0, 0)];
+    assert_eq!(Color::new_rgb(200, 0, 0).nearest(&duplicates), Some(Color::new_rgb(255, 0, 0)));
+    let duplicates = [Color::new_hsv(0, 100, 100), Color::new_rgb(255, 0, 0)];
+    assert_eq!(Color::new_rgb(200, 0, 0).nearest(&duplicates), Some(Color::new_hsv(0, 100, 100)));
+}
+
+#[test]
+fn test_rotate_hue() {
+    let orange = Color::new_hsv(30, 80, 90);
+
+    assert_eq!(orange.rotate_hue(0), orange);
+    assert_eq!(orange.rotate_hue(180), Color::new_hsv(210, 80, 90));
+    assert_eq!(orange.rotate_hue(330), Color::new_hsv(0, 80, 90));
+    assert_eq!(orange.rotate_hue(-30), Color::new_hsv(0, 80, 90));
+    assert_eq!(orange.rotate_hue(-45), Color::new_hsv(345, 80, 90));
+    assert_eq!(orange.rotate_hue(720), orange);
+    assert_eq!(orange.rotate_hue(-1000), Color::new_hsv(110, 80, 90));
+
+    assert_eq!(Color::new_rgb(255, 0, 0).rotate_hue(120), Color::new_hsv(120, 100, 100));
+    assert_eq!(Color::new_rgba(255, 0, 0, 51).rotate_hue(240), Color::new_hsva(240, 100, 100, 20));
+    assert_eq!(Color::new_hsva(30, 80, 90, 50).rotate_hue(10), Color::new_hsva(40, 80, 90, 50));
+    assert_eq!(Color::new_hsl(30, 100, 50).rotate_hue(10), Color::new_hsv(40, 100, 100));
+    assert_eq!(Color::new_rgb(128, 128, 128).rotate_hue(90), Color::new_hsv(90, 0, 50));
+}
+
+#[test]
+fn test_harmony() {
+    let orange = Color::new_hsv(30, 80, 90);
+
+    assert_eq!(orange.harmony(Harmony::Complementary), vec![orange, Color::new_hsv(210, 80, 90)]);
+    assert_eq!(
+        orange.harmony(Harmony::SplitComplementary),
+        vec![orange, Color::new_hsv(180, 80, 90), Color::new_hsv(240, 80, 90)],
+    );
+    assert_eq!(
+        orange.harmony(Harmony::Triadic),
+        vec![orange, Color::new_hsv(150, 80, 90), Color::new_hsv(270, 80, 90)],
+    );
+    assert_eq!(
+        orange.harmony(Harmony::Tetradic),
+        vec![orange, Color::new_hsv(120, 80, 90), Color::new_hsv(210, 80, 90), Color::new_hsv(300, 80, 90)],
+    );
+    assert_eq!(
+        orange.harmony(Harmony::Analogous),
+        vec![orange, Color::new_hsv(0, 80, 90), Color::new_hsv(60, 80, 90)],
+    );
+
+    let red = Color::new_rgb(255, 0, 0);
+    assert_eq!(
+        red.harmony(Harmony::Analogous),
+        vec![Color::new_hsv(0, 100, 100), Color::new_hsv(330, 100, 100), Color::new_hsv(30, 100, 100)],
+    );
+    assert_eq!(
+        Color::new_rgba(0, 0, 255, 255).harmony(Harmony::Complementary),
+        vec![Color::new_hsva(240, 100, 100, 100), Color::new_hsva(60, 100, 100, 100)],
+    );
+}
+
+#[test]
+fn test_ramps() {
+    let orange = Color::new_hsv(30, 80, 90);
+
+    assert_eq!(
+        orange.tints(4),
+        vec![orange, Color::new_hsv(30, 60, 93), Color::new_hsv(30, 40, 95), Color::new_hsv(30, 20, 98)],
+    );
+    assert_eq!(
+        orange.shades(4),
+        vec![orange, Color::new_hsv(30, 80, 68), Color::new_hsv(30, 80, 45), Color::new_hsv(30, 80, 23)],
+    );
+    assert_eq!(
+        orange.tones(4),
+        vec![orange, Color::new_hsv(30, 60, 90), Color::new_hsv(30, 40, 90), Color::new_hsv(30, 20, 90)],
+    );
+    assert_eq!(
+        orange.shades(3),
+        vec![orange, Color::new_hsv(30, 80, 60), Color::new_hsv(30, 80, 30)],
+    );
+
+    assert_eq!(orange.tints(0), vec![]);
+    assert_eq!(orange.shades(1), vec![orange]);
+    assert_eq!(orange.tints(100).len(), 100);
+
+    let red = Color::new_rgba(255, 0, 0, 128);
+    assert_eq!(
+        red.tints(2),
+        vec![Color::new_hsva(0, 100, 100, 50), Color::new_hsva(0, 50, 100, 50)],
+    );
+    assert_eq!(
+        Color::new_hsl(200, 100, 25).shades(2),
+        vec![Color::new_hsv(200, 100, 50), Color::new_hsv(200, 100, 25)],
+    );
+
+    for color in orange.tints(10).iter().chain(&orange.shades(10)).chain(&orange.tones(10)) {
+        assert_eq!(color.unwrap_hsv().0, 30);
+    }
+    for pair in orange.tints(10).windows(2) {
+        assert!(pair[0].relative_luminance() < pair[1].relative_luminance());
+    }
+    for pair in orange.shades(10).windows(2) {
+        assert!(pair[0].relative_luminance() > pair[1].relative_luminance());
+    }
+}
+
+#[test]
+fn test_gradient_new() {
+    let red = Color::new_rgb(255, 0, 0);
+    let blue = Color::new_rgb(0, 0, 255);
+
+    let gradient = Gradient::new(vec![(0.0, red), (0.5, red), (0.5, blue), (1.0, blue)]).unwrap();
+    assert_eq!(gradient.stops(), &[(0.0, red), (0.5, red), (0.5, blue), (1.0, blue)]);
+    assert_eq!(gradient.interpolation(), Interpolation::Rgb);
+    let gradient = gradient.with_interpolation(Interpolation::Oklab);
+    assert_eq!(gradient.interpolation(), Interpolation::Oklab);
+
+    assert!(Gradient::new(vec![(0.3, red)]).is_ok());
+    assert_eq!(Gradient::new(vec![]), Err(GradientError::NoStops));
+    assert_eq!(Gradient::new(vec![(0.0, red), (1.5, blue)]), Err(GradientError::InvalidPosition(1)));
+    assert_eq!(Gradient::new(vec![(-0.1, red), (1.0, blue)]), Err(GradientError::InvalidPosition(0)));
+    assert_eq!(Gradient::new(vec![(0.0, red), (f64::NAN, blue)]), Err(GradientError::InvalidPosition(1)));
+    assert_eq!(
+        Gradient::new(vec![(0.0, red), (0.6, blue), (0.4, red)]),
+        Err(GradientError::InvalidPosition(2)),
+    );
+}
+
+#[test]
+fn test_gradient_sample_rgb() {
+    let red = Color::new_rgb(255, 0, 0);
+    let blue = Color::new_rgb(0, 0, 255);
+    let gradient = Gradient::new(vec![(0.0, red), (1.0, blue)]).unwrap();
+
+    assert_eq!(gradient.sample(0.0), red);
+    assert_eq!(gradient.sample(0.5), Color::new_rgb(128, 0, 128));
+    assert_eq!(gradient.sample(0.5), red.mix(&blue, 0.5));
+    assert_eq!(gradient.sample(0.25), Color::new_rgb(191, 0, 64));
+    assert_eq!(gradient.sample(1.0), blue);
+    assert_eq!(gradient.sample(2.0), blue);
+    assert_eq!(gradient.sample(-2.0), red);
+    assert_eq!(gradient.sample(f64::NAN), red);
+
+    let green = Color::new_hsl(120, 100, 50);
+    let yellow = Color::new_rgb(255, 255, 0);
+    let gradient = Gradient::new(vec![
+        (0.2, green),
+        (0.5, yellow),
+        (0.5, Color::new_rgba(0, 0, 0, 128)),
+        (0.9, blue),
+    ]).unwrap();
+
+    assert_eq!(gradient.sample(0.0), green);
+    assert_eq!(gradient.sample(0.2), green);
+    assert_eq!(gradient.sample(0.4), Color::new_rgb(170, 255, 0));
+    assert_eq!(gradient.sample(0.4999), Color::new_rgb(255, 255, 0));
+    assert_eq!(gradient.sample(0.5), Color::new_rgba(0, 0, 0, 128));
+    assert_eq!(gradient.sample(0.6), Color::new_rgba(0, 0, 64, 160));
+    assert_eq!(gradient.sample(0.95), blue);
+
+    let single = Gradient::new(vec![(0.5, yellow)]).unwrap();
+    assert_eq!(single.sample(0.0), yellow);
+    assert_eq!(single.sample(1.0), yellow);
+}
+
+#[test]
+fn test_gradient_take() {
+    let red = Color::new_rgb(255, 0, 0);
+    let blue = Color::new_rgb(0, 0, 255);
+    let gradient = Gradient::new(vec![(0.0, red), (1.0, blue)]).unwrap();
+
+    assert_eq!(
+        gradient.take(5),
+        vec![red, Color::new_rgb(191, 0, 64), Color::new_rgb(128, 0, 128), Color::new_rgb(64, 0, 191), blue],
+    );
+    assert_eq!(gradient.take(2), vec![red, blue]);
+    assert_eq!(gradient.take(1), vec![red]);
+    assert_eq!(gradient.take(0), vec![]);
+    assert_eq!(gradient.take(101).len(), 101);
+}
+
+#[test]
+fn test_gradient_sample_hsv() {
+    let red = Color::new_rgb(255, 0, 0);
+    let blue = Color::new_rgb(0, 0, 255);
+    let gradient = Gradient::new(vec![(0.0, red), (1.0, blue)]).unwrap();
+
+    assert_eq!(
+        gradient.clone().with_interpolation(Interpolation::HsvShorter).take(5),
+        vec![red, Color::new_hsv(330, 100, 100), Color::new_hsv(300, 100, 100), Color::new_hsv(270, 100, 100), blue],
+    );
+    assert_eq!(
+        gradient.with_interpolation(Interpolation::HsvLonger).take(5),
+        vec![red, Color::new_hsv(60, 100, 100), Color::new_hsv(120, 100, 100), Color::new_hsv(180, 100, 100), blue],
+    );
+
+    let gradient = Gradient::new(vec![(0.0, Color::new_hsv(350, 100, 100)), (1.0, Color::new_hsv(10, 100, 100))]).unwrap();
+    assert_eq!(gradient.clone().with_interpolation(Interpolation::HsvShorter).sample(0.5), Color::new_hsv(0, 100, 100));
+    assert_eq!(gradient.clone().with_interpolation(Interpolation::HsvShorter).sample(0.25), Color::new_hsv(355, 100, 100));
+    assert_eq!(gradient.with_interpolation(Interpolation::HsvLonger).sample(0.5), Color::new_hsv(180, 100, 100));
+
+    let gradient = Gradient::new(vec![(0.0, Color::new_hsv(100, 100, 100)), (1.0, Color::new_hsv(100, 50, 50))]).unwrap();
+    assert_eq!(gradient.clone().with_interpolation(Interpolation::HsvShorter).sample(0.5), Color::new_hsv(100, 75, 75));
+    assert_eq!(gradient.with_interpolation(Interpolation::HsvLonger).sample(0.5), Color::new_hsv(280, 75, 75));
+
+    let gray = Color::new_rgb(128, 128, 128);
+    let gradient = Gradient::new(vec![(0.0, gray), (1.0, Color::new_hsv(200, 100, 100))]).unwrap();
+    assert_eq!(gradient.with_interpolation(Interpolation::HsvShorter).sample(0.5), Color::new_hsv(200, 50, 75));
+
+    let gradient = Gradient::new(vec![(0.0, Color::new_hsl(90, 100, 50)), (1.0, Color::new_hwb(150, 0, 0))]).unwrap();
+    assert_eq!(gradient.with_interpolation(Interpolation::HsvShorter).sample(0.5), Color::new_hsv(120, 100, 100));
+
+    let gradient = Gradient::new(vec![(0.0, red), (1.0, Color::new_rgba(0, 0, 255, 0))]).unwrap();
+    assert_eq!(
+        gradient.with_interpolation(Interpolation::HsvShorter).sample(0.5),
+        Color::new_hsva(300, 100, 100, 50),
+    );
+}
+
+#[test]
+fn test_gradient_sample_oklab() {
+    let red = Color::new_rgb(255, 0, 0);
+    let blue = Color::new_rgb(0, 0, 255);
+    let gradient = Gradient::new(vec![(0.0, red), (1.0, blue)]).unwrap().with_interpolation(Interpolation::Oklab);
+
+    assert_eq!(
+        gradient.take(5),
+        vec![red, Color::new_rgb(198, 73, 109), Color::new_rgb(140, 83, 162), Color::new_rgb(81, 71, 210), blue],
+    );
+
+    let white = Color::new_rgb(255, 255, 255);
+    let black = Color::new_rgb(0, 0, 0);
+    let gradient = Gradient::new(vec![(0.0, white), (1.0, black)]).unwrap().with_interpolation(Interpolation::Oklab);
+    assert_eq!(gradient.sample(0.5), Color::new_rgb(99, 99, 99));
+    let middle = gradient.sample(0.5).to_oklab();
+    assert_close(middle.l, 0.5, 0.005);
+
+    let gradient = Gradient::new(vec![(0.0, red), (1.0, Color::new_rgba(0, 0, 255, 0))])
+        .unwrap()
+        .with_interpolation(Interpolation::Oklab);
+    assert_eq!(gradient.sample(0.5), Color::new_rgba(140, 83, 162, 128));
+    assert_eq!(gradient.sample(0.0), red);
+}
+
+#[test]
+fn test_gradient_to_css() {
+    let red = Color::new_rgb(255, 0, 0);
+    let blue = Color::new_rgb(0, 0, 255);
+    let gradient = Gradient::new(vec![(0.0, red), (1.0, blue)]).unwrap();
+
+    assert_eq!(gradient.to_css(GradientKind::Linear(90)), "linear-gradient(90deg, #ff0000 0%, #0000ff 100%)");
+    assert_eq!(gradient.to_css(GradientKind::Linear(180)), "linear-gradient(180deg, #ff0000 0%, #0000ff 100%)");
+    assert_eq!(gradient.to_css(GradientKind::Radial), "radial-gradient(#ff0000 0%, #0000ff 100%)");
+
+    let oklab = gradient.clone().with_interpolation(Interpolation::Oklab);
+    assert_eq!(oklab.to_css(GradientKind::Linear(45)), "linear-gradient(45deg in oklab, #ff0000 0%, #0000ff 100%)");
+    assert_eq!(oklab.to_css(GradientKind::Radial), "radial-gradient(in oklab, #ff0000 0%, #0000ff 100%)");
+
+    assert_eq!(
+        gradient.clone().with_interpolation(Interpolation::HsvShorter).to_css(GradientKind::Linear(90)),
+        "linear-gradient(90deg, #ff0000 0%, #ff0033 10%, #ff0066 20%, #ff0099 30%, #ff00cc 40%, #ff00ff 50%, \
+         #cc00ff 60%, #9900ff 70%, #6600ff 80%, #3300ff 90%, #0000ff 100%)",
+    );
+    assert_eq!(
+        gradient.with_interpolation(Interpolation::HsvLonger).to_css(GradientKind::Radial),
+        "radial-gradient(#ff0000 0%, #ff6600 10%, #ffcc00 20%, #ccff00 30%, #66ff00 40%, #00ff00 50%, \
+         #00ff66 60%, #00ffcc 70%, #00ccff 80%, #0066ff 90%, #0000ff 100%)",
+    );
+
+    let gradient = Gradient::new(vec![
+        (0.2, Color::new_hsl(120, 100, 50)),
+        (0.5, Color::new_rgb(255, 255, 0)),
+        (0.5, Color::new_rgba(0, 0, 0, 128)),
+        (2.0 / 3.0, blue),
+    ]).unwrap();
+    assert_eq!(
+        gradient.to_css(GradientKind::Radial),
+        "radial-gradient(hsl(120 100% 50%) 20%, #ffff00 50%, #00000080 50%, #0000ff 66.67%)",
+    );
+    let format = ColorFormat { model: Some(ColorModel::Rgb), modern_syntax: true, ..ColorFormat::default() };
+    assert_eq!(
+        gradient.to_css_with(GradientKind::Radial, &format),
+        "radial-gradient(rgb(0 255 0) 20%, rgb(255 255 0) 50%, rgb(0 0 0 / 0.502) 50%, rgb(0 0 255) 66.67%)",
+    );
+    let format = ColorFormat { prefer_name: true, ..ColorFormat::default() };
+    assert_eq!(
+        Gradient::new(vec![(0.125, red), (0.5, blue)]).unwrap().to_css_with(GradientKind::Linear(0), &format),
+        "linear-gradient(0deg, red 12.5%, blue 50%)",
+    );
+
+    let hard = Gradient::new(vec![(0.0, red), (0.5, red), (0.5, blue), (1.0, blue)])
+        .unwrap()
+        .with_interpolation(Interpolation::HsvShorter);
+    let css = hard.to_css(GradientKind::Linear(90));
+    assert_eq!(css.matches("%").count(), 4 + 9 + 9);
+    assert!(css.contains("#ff0000 45%, #ff0000 50%, #0000ff 50%, #0000ff 55%"));
+}
+
+#[test]
+fn test_simulate_cvd() {
+    let red = Color::new_rgb(255, 0, 0);
+    let green = Color::new_rgb(0, 255, 0);
+    let blue = Color::new_rgb(0, 0, 255);
+    let purple = Color::new_rgb(102, 51, 153);
+
+    assert_eq!(red.simulate_cvd(CvdKind::Protanopia, 1.0), Color::new_rgb(109, 95, 0));
+    assert_eq!(green.simulate_cvd(CvdKind::Protanopia, 1.0), Color::new_rgb(255, 229, 0));
+    assert_eq!(blue.simulate_cvd(CvdKind::Protanopia, 1.0), Color::new_rgb(0, 89, 255));
+    assert_eq!(purple.simulate_cvd(CvdKind::Protanopia, 1.0), Color::new_rgb(0, 76, 156));
+
+    assert_eq!(red.simulate_cvd(CvdKind::Deuteranopia, 1.0), Color::new_rgb(163, 144, 0));
+    assert_eq!(green.simulate_cvd(CvdKind::Deuteranopia, 1.0), Color::new_rgb(239, 214, 58));
+    assert_eq!(purple.simulate_cvd(CvdKind::Deuteranopia, 1.0), Color::new_rgb(15, 77, 151));
+
+    assert_eq!(red.simulate_cvd(CvdKind::Tritanopia, 1.0), Color::new_rgb(255, 0, 15));
+    assert_eq!(green.simulate_cvd(CvdKind::Tritanopia, 1.0), Color::new_rgb(0, 247, 217));
+    assert_eq!(blue.simulate_cvd(CvdKind::Tritanopia, 1.0), Color::new_rgb(0, 107, 150));
+
+    assert_eq!(red.simulate_cvd(CvdKind::Achromatopsia, 1.0), Color::new_rgb(127, 127, 127));
+    assert_eq!(green.simulate_cvd(CvdKind::Achromatopsia, 1.0), Color::new_rgb(220, 220, 220));
+    assert_eq!(blue.simulate_cvd(CvdKind::Achromatopsia, 1.0), Color::new_rgb(76, 76, 76));
+}
+
+#[test]
+fn test_simulate_cvd_severity() {
+    let red = Color::new_rgb(255, 0, 0);
+    let green = Color::new_rgb(0, 255, 0);
+
+    assert_eq!(red.simulate_cvd(CvdKind::Protanopia, 0.5), Color::new_rgb(180, 86, 0));
+    assert_eq!(green.simulate_cvd(CvdKind::Deuteranopia, 0.5), Color::new_rgb(205, 229, 46));
+    assert_eq!(green.simulate_cvd(CvdKind::Tritanopia, 0.5), Color::new_rgb(46, 250, 137));
+    assert_eq!(red.simulate_cvd(CvdKind::Achromatopsia, 0.5), Color::new_rgb(204, 92, 92));
+
+    // between the steps of the table
+    assert_eq!(red.simulate_cvd(CvdKind::Protanopia, 0.2), Color::new_rgb(223, 64, 0));
+    assert_eq!(red.simulate_cvd(CvdKind::Protanopia, 0.25), Color::new_rgb(215, 70, 0));
+    assert_eq!(red.simulate_cvd(CvdKind::Protanopia, 0.3), Color::new_rgb(208, 74, 0));
+    assert_eq!(green.simulate_cvd(CvdKind::Tritanopia, 0.75), Color::new_rgb(0, 252, 179));
+
+    assert_eq!(red.simulate_cvd(CvdKind::Protanopia, 2.0), red.simulate_cvd(CvdKind::Protanopia, 1.0));
+    assert_eq!(red.simulate_cvd(CvdKind::Protanopia, -1.0), red);
+    assert_eq!(red.simulate_cvd(CvdKind::Protanopia, f64::NAN), red);
+
+    for kind in [CvdKind::Protanopia, CvdKind::Deuteranopia, CvdKind::Tritanopia, CvdKind::Achromatopsia] {
+        assert_eq!(Color::new_hsv(200, 40, 70).simulate_cvd(kind, 0.0), Color::new_hsv(200, 40, 70).to_rgb());
+
+        for severity in [0.25, 0.3, 1.0] {
+            for value in 0..=255 {
+                let gray = Color::new_rgb(value, value, value);
+                assert_eq!(gray.simulate_cvd(kind, severity), gray);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_simulate_cvd_variants() {
+    let red = Color::new_rgb(255, 0, 0);
+
+    assert_eq!(Color::new_hsv(0, 100, 100).simulate_cvd(CvdKind::Protanopia, 1.0), Color::new_rgb(109, 95, 0));
+    assert_eq!(Color::new_hsl(0, 100, 50).simulate_cvd(CvdKind::Protanopia, 1.0), Color::new_rgb(109, 95, 0));
+    assert_eq!(Color::new_rgba(255, 0, 0, 100).simulate_cvd(CvdKind::Protanopia, 1.0), Color::new_rgba(109, 95, 0, 100));
+    assert_eq!(Color::new_hsva(0, 100, 100, 50).simulate_cvd(CvdKind::Achromatopsia, 1.0), Color::new_rgba(127, 127, 127, 128));
+    assert_eq!(Color::new_rgba(255, 0, 0, 255).simulate_cvd(CvdKind::Tritanopia, 0.0), Color::new_rgba(255, 0, 0, 255));
+    assert_eq!(red.simulate_cvd(CvdKind::Tritanopia, 0.0), red);
+}
+
+#[test]
+fn test_confusable_pairs() {
+    let palette = [
+        Color::parse("#d62728").unwrap(),
+        Color::parse("#2ca02c").unwrap(),
+        Color::parse("#1f77b4").unwrap(),
+        Color::parse("#ff7f0e").unwrap(),
+        Color::parse("#8c564b").unwrap(),
+        Color::parse("#9467bd").unwrap(),
+    ];
+
+    assert_eq!(Color::confusable_pairs(&palette, CvdKind::Protanopia, 1.0, 10.0), vec![(0, 4), (1, 3), (2, 5)]);
+    assert_eq!(Color::confusable_pairs(&palette, CvdKind::Deuteranopia, 1.0, 10.0), vec![(0, 1), (2, 5)]);
+    assert_eq!(Color::confusable_pairs(&palette, CvdKind::Tritanopia, 1.0, 10.0), vec![]);
+    assert_eq!(Color::confusable_pairs(&palette, CvdKind::Protanopia, 0.5, 10.0), vec![(2, 5)]);
+    assert_eq!(Color::confusable_pairs(&palette, CvdKind::Protanopia, 0.0, 10.0), vec![]);
+    assert_eq!(
+        Color::confusable_pairs(&palette, CvdKind::Achromatopsia, 1.0, 10.0),
+        vec![(0, 2), (0, 4), (0, 5), (1, 2), (1, 3), (1, 5), (2, 4), (2, 5), (4, 5)],
+    );
+
+    let red = Color::new_rgb(255, 0, 0);
+    let green = Color::new_rgb(0, 255, 0);
+    let almost_red = Color::new_rgb(250, 0, 0);
+    assert_eq!(Color::confusable_pairs(&[red, almost_red], CvdKind::Achromatopsia, 1.0, 5.0), vec![]);
+    assert_eq!(Color::confusable_pairs(&[red, green], CvdKind::Protanopia, 1.0, 1.0), vec![]);
+    assert_eq!(Color::confusable_pairs(&[], CvdKind::Protanopia, 1.0, 10.0), vec![]);
+    assert_eq!(Color::confusable_pairs(&[red], CvdKind::Protanopia, 1.0, 10.0), vec![]);
+}
+
+#[test]
+fn test_css_find_colors_basic() {
+    let text = "a:hover, #add { color: #ABC; background: url(#abc) RGB(1, 2, 3) } /* red */";
+    let found = css::find_colors(text);
+
+    assert_eq!(
+        found,
+        vec![
+            css::ColorMatch { span: 23..27, color: Color::new_rgb(0xaa, 0xbb, 0xcc) },
+            css::ColorMatch { span: 51..63, color: Color::new_rgb(1, 2, 3) },
+        ],
+    );
+    assert_eq!(&text[found[0].span.clone()], "#ABC");
+    assert_eq!(&text[found[1].span.clone()], "RGB(1, 2, 3)");
+
+    assert_eq!(css::find_colors(""), vec![]);
+    assert_eq!(css::find_colors("   /* color: red; */  "), vec![]);
+
+    let text = "color: red";
+    assert_eq!(css::find_colors(text), vec![css::ColorMatch { span: 7..10, color: Color::new_rgb(255, 0, 0) }]);
+    let text = "color:blue;background:#0f08";
+    assert_eq!(
+        css::find_colors(text),
+        vec![
+            css::ColorMatch { span: 6..10, color: Color::new_rgb(0, 0, 255) },
+            css::ColorMatch { span: 22..27, color: Color::new_rgba(0, 255, 0, 136) },
+        ],
+    );
+}
+
+fn css_colors(text: &str) -> Vec<&str> {
+    css::find_colors(text).into_iter().map(|found| &text[found.span]).collect()
+}
+
+#[test]
+fn test_css_find_colors_kinds() {
+    assert_eq!(
+        css_colors("p { color: #fff; color: #FFFF; color: #abcdef; color: #AbCdEf80 }"),
+        vec!["#fff", "#FFFF", "#abcdef", "#AbCdEf80"],
+    );
+    assert_eq!(css_colors("p { color: #12345; color: #1234567; color: #ff; color: #fffe-x; color: #ggg }"), Vec::<&str>::new());
+
+    assert_eq!(
+        css_colors("p { a: rgb(1,2,3); b: rgba(1, 2, 3, 0.5); c: hsl(120 50% 50% / 0.5); d: HWB(0 0% 0%); e: hsv(1,2%,3%) }"),
+        vec!["rgb(1,2,3)", "rgba(1, 2, 3, 0.5)", "hsl(120 50% 50% / 0.5)", "HWB(0 0% 0%)", "hsv(1,2%,3%)"],
+    );
+    assert_eq!(
+        css_colors("p { a: rgb(var(--r), 0, 0); b: rgb(10.5 2 3); c: rgb(300, 0, 0) red; d: rgb(from red r g b) }"),
+        vec!["red"],
+    );
+
+    assert_eq!(
+        css_colors("p { color: Red; background: REBECCAPURPLE; border-color: transparent; fill: currentColor }"),
+        vec!["Red", "REBECCAPURPLE", "transparent"],
+    );
+    assert_eq!(
+        css_colors("p { color: red-ish; border: 1px solid red; margin: -0.5em; --x: 10red; animation: tan 1s }"),
+        vec!["red", "tan"],
+    );
+    assert_eq!(
+        css_colors("p { background: linear-gradient(90deg, red 0%, rgba(0,0,255,0.5) 100%), var(--x, navy) }"),
+        vec!["red", "rgba(0,0,255,0.5)", "navy"],
+    );
+    assert_eq!(css_colors("p { font-family: \"Red Hat\", Tomato }"), vec!["Tomato"]);
+}
+
+#[test]
+fn test_css_find_colors_context() {
+    assert_eq!(css_colors("#add, .red:not(#fed), a:hover { color: #bad }"), vec!["#bad"]);
+    assert_eq!(css_colors("red { color: blue } #fff{color:#000}"), vec!["blue", "#000"]);
+    assert_eq!(
+        css_colors("@media (prefers-color-scheme: dark) { :root { --bg: #111; } a:hover, b:focus { color: white } }"),
+        vec!["#111", "white"],
+    );
+    assert_eq!(css_colors("@import url(\"red.css\"); @import url(#fff); p { color: red }"), vec!["red"]);
+
+    assert_eq!(css_colors("p { content: \"#fff red\"; color: 'blue' }"), Vec::<&str>::new());
+    assert_eq!(css_colors("p { content: \"\\\"red\\\"\"; color: green }"), vec!["green"]);
+    assert_eq!(css_colors("p { content: 'red\n; color: green }"), vec!["green"]);
+    assert_eq!(css_colors("p { color: /* red */ blue /* #fff */ }"), vec!["blue"]);
+    assert_eq!(css_colors("p { color /* : red */ : blue }"), vec!["blue"]);
+    assert_eq!(css_colors("p { color: blue /* unterminated red"), vec!["blue"]);
+    assert_eq!(css_colors("p { background: url(#fff) url( 'red' ) red }"), vec!["red"]);
+    assert_eq!(css_colors("p { mask: url(data:image/png;base64,red) blue }"), vec!["blue"]);
+    assert_eq!(css_colors("p { color: red !important }"), vec!["red"]);
+    assert_eq!(css_colors("p { --ünïcödé: red; color: hsl(0,0%,0%) }"), vec!["red", "hsl(0,0%,0%)"]);
+}
+
+#[test]
+fn test_css_rewrite() {
+    let text = "a:hover, #add { color: #ABC; background: url(#abc) RGB(1, 2, 3) } /* red */";
+    assert_eq!(
+        css::rewrite(text, |color| color.invert()),
+        "a:hover, #add { color: #554433; background: url(#abc) #fefdfc } /* red */",
+    );
+    assert_eq!(
+        css::rewrite(text, |color| color),
+        "a:hover, #add { color: #aabbcc; background: url(#abc) #010203 } /* red */",
+    );
+
+    let text = "/* theme */\n.x {\n  border: 1px solid rebeccapurple;\n  content: \"#fff\";\n  --brand: hsl(120 100% 25%);\n}\n";
+    let mut seen = Vec::new();
+    let rewritten = css::rewrite(text, |color| {
+        seen.push(color);
+        color.to_rgb()
+    });
+    assert_eq!(rewritten, "/* theme */\n.x {\n  border: 1px solid #663399;\n  content: \"#fff\";\n  --brand: #008000;\n}\n");
+    assert_eq!(seen, vec![Color::new_rgb(102, 51, 153), Color::new_hsl(120, 100, 25)]);
+
+    let format = ColorFormat { model: Some(ColorModel::Rgb), space_after_comma: true, ..ColorFormat::default() };
+    assert_eq!(
+        css::rewrite_with("p{color:#f008;background:red}", &format, |color| color),
+        "p{color:rgba(255, 0, 0, 0.533);background:rgb(255, 0, 0)}",
+    );
+    let format = ColorFormat { prefer_name: true, ..ColorFormat::default() };
+    assert_eq!(
+        css::rewrite_with("p { color: #f00; background: rgb(0 0 0 / 50%) }", &format, |color| color.invert()),
+        "p { color: aqua; background: #ffffff80 }",
+    );
+
+    let untouched = "@media screen { #fed:hover { content: 'red'; } }\n/* #fff */ a { }";
+    assert_eq!(css::rewrite(untouched, |_| Color::new_rgb(0, 0, 0)), untouched);
+    assert_eq!(css::rewrite("", |color| color), "");
+}
+
+#[cfg(feature = "serde")]
+mod serde_tests {
+    use serde::{Deserialize, Serialize};
+    use solution::*;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Palette {
+        background: Color,
+        #[serde(with = "solution::structured")]
+        accent: Color,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Structured {
+        #[serde(with = "solution::structured")]
+        color: Color,
+    }
+
+    fn structured(json: &str) -> Result<Color, serde_json::Error> {
+        serde_json::from_str::<Structured>(&format!("{{\"color\":{}}}", json)).map(|s| s.color)
+    }
+
+    #[test]
+    fn test_serde_string() {
+        let colors = [
+            Color::new_rgb(255, 0, 0),
+            Color::new_rgba(1, 2, 3, 4),
+            Color::new_hsv(120, 50, 50),
+            Color::new_hsva(120, 50, 50, 25),
+            Color::new_hsl(200, 100, 30),
+            Color::new_hwb(10, 20, 30),
+        ];
+        for color in colors {
+            let json = serde_json::to_string(&color).unwrap();
+            assert_eq!(json, format!("\"{}\"", color));
+            assert_eq!(serde_json::from_str::<Color>(&json).unwrap(), color);
+        }
+
+        assert_eq!(serde_json::to_string(&Color::new_hsv(120, 50, 50)).unwrap(), "\"hsv(120,50%,50%)\"");
+        assert_eq!(serde_json::from_str::<Color>("\"rgb(1, 2, 3)\"").unwrap(), Color::new_rgb(1, 2, 3));
+        assert_eq!(serde_json::from_str::<Color>("\" #FFF \"").unwrap(), Color::new_rgb(255, 255, 255));
+
+        assert!(serde_json::from_str::<Color>("\"#ff\"").is_err());
+        assert!(serde_json::from_str::<Color>("\"hsv(361,0%,0%)\"").is_err());
+        assert!(serde_json::from_str::<Color>("\"red\"").is_err());
+        assert!(serde_json::from_str::<Color>("16711680").is_err());
+        assert!(serde_json::from_str::<Color>("null").is_err());
+        assert!(serde_json::from_str::<Color>("{\"rgb\":[1,2,3]}").is_err());
+    }
+
+    #[test]
+    fn test_serde_structured() {
+        let cases = [
+            (Color::new_rgb(255, 0, 0), "{\"rgb\":[255,0,0]}"),
+            (Color::new_rgba(255, 0, 0, 128), "{\"rgba\":[255,0,0,128]}"),
+            (Color::new_hsv(120, 50, 50), "{\"hsv\":{\"hue\":120,\"saturation\":50,\"value\":50}}"),
+            (Color::new_hsva(120, 50, 50, 50), "{\"hsva\":{\"hue\":120,\"saturation\":50,\"value\":50,\"alpha\":50}}"),
+            (Color::new_hsl(120, 50, 50), "{\"hsl\":{\"hue\":120,\"saturation\":50,\"lightness\":50}}"),
+            (Color::new_hwb(120, 50, 50), "{\"hwb\":{\"hue\":120,\"whiteness\":50,\"blackness\":50}}"),
+        ];
+        for (color, json) in cases {
+            let value = serde_json::to_string(&Structured { color }).unwrap();
+            assert_eq!(value, format!("{{\"color\":{}}}", json));
+            assert_eq!(structured(json).unwrap(), color);
+        }
+
+        let palette = Palette { background: Color::new_rgb(0, 0, 0), accent: Color::new_hsl(200, 100, 50) };
+        let json = serde_json::to_string(&palette).unwrap();
+        assert_eq!(json, "{\"background\":\"#000000\",\"accent\":{\"hsl\":{\"hue\":200,\"saturation\":100,\"lightness\":50}}}");
+        assert_eq!(serde_json::from_str::<Palette>(&json).unwrap(), palette);
+    }
+
+    #[test]
+    fn test_serde_structured_errors() {
+        assert!(structured("{\"hsv\":{\"hue\":360,\"saturation\":100,\"value\":100}}").is_ok());
+        assert!(structured("{\"hsv\":{\"hue\":361,\"saturation\":0,\"value\":0}}").is_err());
+        assert!(structured("{\"hsv\":{\"hue\":0,\"saturation\":101,\"value\":0}}").is_err());
+        assert!(structured("{\"hsv\":{\"hue\":0,\"saturation\":0,\"value\":101}}").is_err());
+        assert!(structured("{\"hsva\":{\"hue\":0,\"saturation\":0,\"value\":0,\"alpha\":101}}").is_err());
+        assert!(structured("{\"hsl\":{\"hue\":0,\"saturation\":0,\"lightness\":101}}").is_err());
+        assert!(structured("{\"hwb\":{\"hue\":0,\"whiteness\":101,\"blackness\":0}}").is_err());
+        assert!(structured("{\"hwb\":{\"hue\":0,\"whiteness\":60,\"blackness\":60}}").is_ok());
+
+        assert!(structured("{\"rgb\":[256,0,0]}").is_err());
+        assert!(structured("{\"rgb\":[-1,0,0]}").is_err());
+        assert!(structured("{\"rgb\":[1,2]}").is_err());
+        assert!(structured("{\"rgb\":[1,2,3,4]}").is_err());
+        assert!(structured("{\"hsv\":{\"hue\":-1,\"saturation\":0,\"value\":0}}").is_err());
+        assert!(structured("{\"hsv\":{\"hue\":0,\"saturation\":0}}").is_err());
+        assert!(structured("{\"hsv\":{\"hue\":0,\"saturation\":0,\"value\":0,\"alpha\":0}}").is_err());
+        assert!(structured("{\"cmyk\":[0,0,0,0]}").is_err());
+        assert!(structured("{\"RGB\":[1,2,3]}").is_err());
+        assert!(structured("\"#ff0000\"").is_err());
+
+        let error = structured("{\"hsv\":{\"hue\":361,\"saturation\":0,\"value\":0}}").unwrap_err();
+        assert!(error.to_string().contains(&ColorError::HueOutOfRange(361).to_string()));
+    }
+}
+
+#[test]
+fn test_color_filter() {
+    let red = Color::new_rgb(255, 0, 0);
+    let blue = Color::new_rgb(10, 128, 200);
+
+    assert_eq!(red.filter(Filter::Invert), Color::new_rgb(0, 255, 255));
+    assert_eq!(blue.filter(Filter::Invert), Color::new_rgb(245, 127, 55));
+    assert_eq!(red.filter(Filter::Grayscale(1.0)), Color::new_rgb(54, 54, 54));
+    assert_eq!(blue.filter(Filter::Grayscale(1.0)), Color::new_rgb(108, 108, 108));
+    assert_eq!(red.filter(Filter::Grayscale(0.5)), Color::new_rgb(155, 27, 27));
+    assert_eq!(blue.filter(Filter::Brightness(1.5)), Color::new_rgb(15, 192, 255));
+    assert_eq!(blue.filter(Filter::Brightness(0.5)), Color::new_rgb(5, 64, 100));
+    assert_eq!(blue.filter(Filter::Contrast(2.0)), Color::new_rgb(0, 129, 255));
+    assert_eq!(blue.filter(Filter::Contrast(0.5)), Color::new_rgb(69, 128, 164));
+    assert_eq!(red.filter(Filter::HueRotate(90)), Color::new_rgb(128, 255, 0));
+    assert_eq!(blue.filter(Filter::HueRotate(90)), Color::new_rgb(177, 10, 199));
+    assert_eq!(blue.filter(Filter::HueRotate(90)), blue.rotate_hue(90).to_rgb());
+
+    assert_eq!(Color::new_hsv(200, 50, 50).filter(Filter::Invert), Color::new_rgb(191, 149, 127));
+    assert_eq!(Color::new_rgba(10, 128, 200, 77).filter(Filter::Contrast(2.0)), Color::new_rgba(0, 129, 255, 77));
+    assert_eq!(Color::new_rgba(10, 128, 200, 77).filter(Filter::HueRotate(90)), Color::new_rgba(177, 10, 199, 77));
+    assert_eq!(Color::new_hsva(0, 100, 100, 50).filter(Filter::Invert), Color::new_rgba(0, 255, 255, 128));
+}
+
+#[test]
+fn test_color_filter_amounts() {
+    let blue = Color::new_rgb(10, 128, 200);
+
+    assert_eq!(blue.filter(Filter::Grayscale(0.0)), blue);
+    assert_eq!(blue.filter(Filter::Grayscale(5.0)), blue.filter(Filter::Grayscale(1.0)));
+    assert_eq!(blue.filter(Filter::Grayscale(-1.0)), blue);
+    assert_eq!(blue.filter(Filter::Grayscale(f64::NAN)), blue);
+    assert_eq!(blue.filter(Filter::Brightness(1.0)), blue);
+    assert_eq!(blue.filter(Filter::Brightness(0.0)), Color::new_rgb(0, 0, 0));
+    assert_eq!(blue.filter(Filter::Brightness(-2.0)), Color::new_rgb(0, 0, 0));
+    assert_eq!(blue.filter(Filter::Brightness(f64::NAN)), blue);
+    assert_eq!(blue.filter(Filter::Brightness(100.0)), Color::new_rgb(255, 255, 255));
+    assert_eq!(blue.filter(Filter::Contrast(1.0)), blue);
+    assert_eq!(blue.filter(Filter::Contrast(0.0)), Color::new_rgb(128, 128, 128));
+    assert_eq!(blue.filter(Filter::Contrast(-1.0)), Color::new_rgb(128, 128, 128));
+    assert_eq!(blue.filter(Filter::Contrast(f64::NAN)), blue);
+    assert_eq!(blue.filter(Filter::HueRotate(360)), blue.rotate_hue(0).to_rgb());
+    assert_eq!(Color::new_hsv(200, 50, 50).filter(Filter::Brightness(1.0)), Color::new_hsv(200, 50, 50).to_rgb());
+}
+
+fn test_buffer() -> Vec<u8> {
+    (0..255u8).flat_map(|x| [x, 255 - x, x / 2, x.wrapping_mul(7)]).collect()
+}
+
+#[test]
+fn test_pixels_apply_matches_filter() {
+    let filters = [
+        Filter::Invert,
+        Filter::Grayscale(0.3),
+        Filter::Grayscale(1.0),
+        Filter::Brightness(1.7),
+        Filter::Brightness(0.25),
+        Filter::Contrast(1.3),
+        Filter::Contrast(0.1),
+        Filter::HueRotate(-45),
+        Filter::HueRotate(200),
+    ];
+    let original = test_buffer();
+
+    for filter in filters {
+        let mut rgba = original.clone();
+        pixels::apply(&mut rgba, pixels::Layout::Rgba, filter).unwrap();
+        for (after, before) in rgba.chunks(4).zip(original.chunks(4)) {
+            let expected: (u8, u8, u8, u8) = Color::new_rgba(before[0], before[1], before[2], before[3]).filter(filter).into();
+            assert_eq!((after[0], after[1], after[2], after[3]), expected);
+        }
+
+        let mut rgb = original.clone();
+        pixels::apply(&mut rgb, pixels::Layout::Rgb, filter).unwrap();
+        for (after, before) in rgb.chunks(3).zip(original.chunks(3)) {
+            let expected: (u8, u8, u8) = Color::new_rgb(before[0], before[1], before[2]).filter(filter).into();
+            assert_eq!((after[0], after[1], after[2]), expected);
+        }
+    }
+}
+
+#[test]
+fn test_pixels_apply() {
+    let mut buffer = vec![255, 0, 0, 10, 128, 200];
+    pixels::apply(&mut buffer, pixels::Layout::Rgb, Filter::Invert).unwrap();
+    assert_eq!(buffer, vec![0, 255, 255, 245, 127, 55]);
+
+    let mut buffer = vec![255, 0, 0, 77, 10, 128, 200, 0];
+    pixels::apply(&mut buffer, pixels::Layout::Rgba, Filter::Grayscale(1.0)).unwrap();
+    assert_eq!(buffer, vec![54, 54, 54, 77, 108, 108, 108, 0]);
+
+    let mut empty: Vec<u8> = vec![];
+    assert_eq!(pixels::apply(&mut empty, pixels::Layout::Rgba, Filter::Invert), Ok(()));
+
+    let mut partial = vec![1, 2, 3, 4, 5];
+    assert_eq!(pixels::apply(&mut partial, pixels::Layout::Rgb, Filter::Invert), Err(pixels::BufferError::PartialPixel));
+    assert_eq!(pixels::apply(&mut partial, pixels::Layout::Rgba, Filter::Invert), Err(pixels::BufferError::PartialPixel));
+    assert_eq!(partial, vec![1, 2, 3, 4, 5]);
+
+    assert_eq!(pixels::Layout::Rgb.channels(), 3);
+    assert_eq!(pixels::Layout::Rgba.channels(), 4);
+}
+
+#[test]
+fn test_pixels_hsv() {
+    let original = test_buffer();
+
+    let hsv = pixels::to_hsv(&original, pixels::Layout::Rgba).unwrap();
+    assert_eq!(hsv.len(), 255);
+    for (pixel, rgb) in hsv.iter().zip(original.chunks(4)) {
+        assert_eq!(*pixel, Color::new_rgb(rgb[0], rgb[1], rgb[2]).to_hsv().unwrap_hsv());
+    }
+    let hsv_rgb = pixels::to_hsv(&original, pixels::Layout::Rgb).unwrap();
+    assert_eq!(hsv_rgb.len(), 340);
+    assert_eq!(hsv_rgb[1], Color::new_rgb(original[3], original[4], original[5]).to_hsv().unwrap_hsv());
+
+    let mut buffer = original.clone();
+    pixels::from_hsv(&hsv, &mut buffer, pixels::Layout::Rgba).unwrap();
+    for ((after, before), &(h, s, v)) in buffer.chunks(4).zip(original.chunks(4)).zip(&hsv) {
+        let (r, g, b) = Color::new_hsv(h, s, v).to_rgb().unwrap_rgb();
+        assert_eq!(after, &[r, g, b, before[3]]);
+    }
+
+    let mut buffer = vec![0; 6];
+    pixels::from_hsv(&[(0, 100, 100), (240, 100, 50)], &mut buffer, pixels::Layout::Rgb).unwrap();
+    assert_eq!(buffer, vec![255, 0, 0, 0, 0, 128]);
+    assert_eq!(pixels::to_hsv(&buffer, pixels::Layout::Rgb).unwrap(), vec![(0, 100, 100), (240, 100, 50)]);
+
+    let mut buffer = vec![9; 8];
+    assert_eq!(
+        pixels::from_hsv(&[(0, 100, 100)], &mut buffer, pixels::Layout::Rgba),
+        Err(pixels::BufferError::SizeMismatch),
+    );
+    assert_eq!(
+        pixels::from_hsv(&[(0, 100, 100), (361, 0, 0)], &mut buffer, pixels::Layout::Rgba),
+        Err(pixels::BufferError::OutOfRange(1)),
+    );
+    assert_eq!(
+        pixels::from_hsv(&[(0, 101, 100), (0, 0, 101)], &mut buffer, pixels::Layout::Rgba),
+        Err(pixels::BufferError::OutOfRange(0)),
+    );
+    assert_eq!(buffer, vec![9; 8]);
+    assert_eq!(pixels::to_hsv(&[1, 2], pixels::Layout::Rgb), Err(pixels::BufferError::PartialPixel));
+    assert_eq!(pixels::from_hsv(&[], &mut [1, 2], pixels::Layout::Rgb), Err(pixels::BufferError::PartialPixel));
+    assert_eq!(pixels::to_hsv(&[], pixels::Layout::Rgb), Ok(vec![]));
+}
+
+#[test]
+fn test_image() {
+    let image = pixels::Image::new(2, 1, vec![255, 0, 0, 0, 0, 255]).unwrap();
+    assert_eq!(image.width(), 2);
+    assert_eq!(image.height(), 1);
+    assert_eq!(image.data(), &[255, 0, 0, 0, 0, 255]);
+    assert_eq!(image.pixel(0, 0), Some(Color::new_rgb(255, 0, 0)));
+    assert_eq!(image.pixel(1, 0), Some(Color::new_rgb(0, 0, 255)));
+    assert_eq!(image.pixel(2, 0), None);
+    assert_eq!(image.pixel(0, 1), None);
+
+    let mut image = image;
+    image.apply(Filter::Invert);
+    assert_eq!(image.data(), &[0, 255, 255, 255, 255, 0]);
+    image.data_mut()[0] = 7;
+    assert_eq!(image.pixel(0, 0), Some(Color::new_rgb(7, 255, 255)));
+
+    assert_eq!(pixels::Image::new(2, 2, vec![0; 12]).map(|i| i.height()), Ok(2));
+    assert_eq!(pixels::Image::new(0, 0, vec![]).map(|i| i.width()), Ok(0));
+    assert_eq!(pixels::Image::new(2, 2, vec![0; 9]), Err(pixels::BufferError::SizeMismatch));
+    assert_eq!(pixels::Image::new(2, 2, vec![0; 13]), Err(pixels::BufferError::PartialPixel));
+    assert_eq!(pixels::Image::new(usize::MAX, 2, vec![0; 6]), Err(pixels::BufferError::SizeMismatch));
+}
+
+#[test]
+fn test_ppm() {
+    let image = pixels::Image::new(2, 2, vec![255, 0, 0, 0, 255, 0, 0, 0, 255, 1, 2, 3]).unwrap();
+    let bytes = image.to_ppm();
+    assert_eq!(&bytes[..11], b"P6\n2 2\n255\n");
+    assert_eq!(&bytes[11..], image.data());
+    assert_eq!(pixels::Image::from_ppm(&bytes), Ok(image.clone()));
+
+    let bytes = b"P6 # comment\n2\t1 # another one\n\n# and another\n255\r\x00\x01\x02\x0a\x20\xff trailing";
+    let read = pixels::Image::from_ppm(bytes).unwrap();
+    assert_eq!((read.width(), read.height()), (2, 1));
+    assert_eq!(read.data(), &[0, 1, 2, 10, 32, 255]);
+
+    let read = pixels::Image::from_ppm(b"P6\n2 1\n15\n\x00\x0f\x07\x0f\x00\x08").unwrap();
+    assert_eq!(read.data(), &[0, 255, 119, 255, 0, 136]);
+    let read = pixels::Image::from_ppm(b"P6\n1 1\n1\n\x00\x01\x01").unwrap();
+    assert_eq!(read.data(), &[0, 255, 255]);
+
+    let mut inverted = image.clone();
+    inverted.apply(Filter::Invert);
+    let mut bytes = image.to_ppm();
+    pixels::apply(&mut bytes[11..], pixels::Layout::Rgb, Filter::Invert).unwrap();
+    assert_eq!(pixels::Image::from_ppm(&bytes), Ok(inverted));
+}
+
+#[test]
+fn test_ppm_errors() {
+    use pixels::{Image, PpmError};
+
+    assert_eq!(Image::from_ppm(b""), Err(PpmError::NotP6));
+    assert_eq!(Image::from_ppm(b"P3\n1 1\n255\n0 0 0"), Err(PpmError::NotP6));
+    assert_eq!(Image::from_ppm(b"p6\n1 1\n255\n\x00\x00\x00"), Err(PpmError::NotP6));
+    assert_eq!(Image::from_ppm(b"P6"), Err(PpmError::InvalidHeader));
+    assert_eq!(Image::from_ppm(b"P61 1 255\n\x00\x00\x00"), Err(PpmError::InvalidHeader));
+    assert_eq!(Image::from_ppm(b"P6\n1\n"), Err(PpmError::InvalidHeader));
+    assert_eq!(Image::from_ppm(b"P6\n1 x 255\n\x00\x00\x00"), Err(PpmError::InvalidHeader));
+    assert_eq!(Image::from_ppm(b"P6\n1 1 255"), Err(PpmError::InvalidHeader));
+    assert_eq!(Image::from_ppm(b"P6\n0 1 255\n"), Err(PpmError::InvalidHeader));
+    assert_eq!(Image::from_ppm(b"P6\n1 1 -1\n\x00\x00\x00"), Err(PpmError::InvalidHeader));
+    assert_eq!(Image::from_ppm(b"P6\n99999999999 1 255\n"), Err(PpmError::InvalidHeader));
+    assert_eq!(Image::from_ppm(b"P6\n1 1 256\n\x00\x00\x00"), Err(PpmError::UnsupportedMaxval(256)));
+    assert_eq!(Image::from_ppm(b"P6\n1 1 65535\n\x00\x00\x00\x00\x00\x00"), Err(PpmError::UnsupportedMaxval(65535)));
+    assert_eq!(Image::from_ppm(b"P6\n1 1 0\n\x00\x00\x00"), Err(PpmError::UnsupportedMaxval(0)));
+    assert_eq!(Image::from_ppm(b"P6\n2 1 255\n\x00\x00\x00"), Err(PpmError::TooShort));
+    assert_eq!(Image::from_ppm(b"P6\n1 1 255\n"), Err(PpmError::TooShort));
+    assert_eq!(Image::from_ppm(b"P6\n1 1 15\n\x00\x10\x00"), Err(PpmError::SampleAboveMaxval));
+
+    assert_eq!(Image::from_ppm(b"P6\n1 1 255\n\x00\x00\x00").map(|i| i.width()), Ok(1));
+}
+
+fn palette_test_buffer() -> Vec<u8> {
+    let colors = [
+        (255, 0, 0, 5),
+        (250, 10, 0, 3),
+        (0, 0, 255, 4),
+        (10, 10, 240, 2),
+        (0, 200, 0, 6),
+        (255, 255, 255, 1),
+    ];
+    let mut buffer = vec![];
+    for &(r, g, b, n) in &colors {
+        for _ in 0..n {
+            buffer.extend_from_slice(&[r, g, b]);
+        }
+    }
+    buffer
+}
+
+fn palette_strings(palette: &[(Color, usize)]) -> Vec<(String, usize)> {
+    palette.iter().map(|(c, n)| (c.to_string(), *n)).collect()
+}
+
+fn owned(palette: &[(&str, usize)]) -> Vec<(String, usize)> {
+    palette.iter().map(|(c, n)| (c.to_string(), *n)).collect()
+}
+
+#[test]
+fn test_palette_median_cut() {
+    use pixels::{palette, Layout, PaletteOrder, Quantizer};
+
+    let buffer = palette_test_buffer();
+    let cut = |k, order| palette_strings(&palette(&buffer, Layout::Rgb, k, Quantizer::MedianCut, order).unwrap());
+
+    assert_eq!(cut(1, PaletteOrder::Population), owned(&[("#6e4854", 21)]));
+    assert_eq!(cut(2, PaletteOrder::Population), owned(&[("#d11c43", 11), ("#007866", 10)]));
+    assert_eq!(cut(2, PaletteOrder::Hue), owned(&[("#007866", 10), ("#d11c43", 11)]));
+    assert_eq!(cut(3, PaletteOrder::Population), owned(&[("#d11c43", 11), ("#00c800", 6), ("#0000ff", 4)]));
+    assert_eq!(cut(4, PaletteOrder::Population), owned(&[("#00c800", 6), ("#ab337b", 6), ("#ff0000", 5), ("#0000ff", 4)]));
+
+    let all = owned(&[("#00c800", 6), ("#ff0000", 5), ("#0000ff", 4), ("#fa0a00", 3), ("#0a0af0", 2), ("#ffffff", 1)]);
+    assert_eq!(cut(6, PaletteOrder::Population), all);
+    assert_eq!(cut(100, PaletteOrder::Population), all);
+    assert_eq!(
+        cut(6, PaletteOrder::Hue),
+        owned(&[("#ff0000", 5), ("#fa0a00", 3), ("#00c800", 6), ("#0000ff", 4), ("#0a0af0", 2), ("#ffffff", 1)]),
+    );
+}
+
+#[test]
+fn test_palette_k_means() {
+    use pixels::{palette, Layout, PaletteOrder, Quantizer};
+
+    let buffer = palette_test_buffer();
+    let means = |k, seed, iterations| {
+        let quantizer = Quantizer::KMeans { seed, iterations };
+        palette_strings(&palette(&buffer, Layout::Rgb, k, quantizer, PaletteOrder::Population).unwrap())
+    };
+
+    assert_eq!(means(1, 0, 10), owned(&[("#6e4854", 21)]));
+    assert_eq!(means(2, 0, 10), owned(&[("#157187", 13), ("#fd0400", 8)]));
+    assert_eq!(means(2, 42, 10), owned(&[("#986311", 15), ("#0303fa", 6)]));
+    assert_eq!(means(3, 0, 10), owned(&[("#02667d", 12), ("#fd0400", 8), ("#ffffff", 1)]));
+    assert_eq!(means(3, 42, 10), owned(&[("#fd201c", 9), ("#00c800", 6), ("#0303fa", 6)]));
+    assert_eq!(means(4, 42, 10), owned(&[("#fd0400", 8), ("#00c800", 6), ("#0303fa", 6), ("#ffffff", 1)]));
+    assert_eq!(means(4, 42, 1), owned(&[("#fd0400", 8), ("#00c800", 6), ("#0000ff", 4), ("#5c5cf5", 3)]));
+    assert_eq!(means(4, 42, 0), means(4, 42, 1));
+    assert_eq!(means(2, 7, 10), means(2, 7, 10));
+
+    let all = owned(&[("#00c800", 6), ("#ff0000", 5), ("#0000ff", 4), ("#fa0a00", 3), ("#0a0af0", 2), ("#ffffff", 1)]);
+    assert_eq!(means(6, 0, 10), all);
+    assert_eq!(means(6, 42, 0), all);
+    assert_eq!(means(100, 1, 10), all);
+
+    let hue = palette(&buffer, Layout::Rgb, 3, Quantizer::KMeans { seed: 0, iterations: 10 }, PaletteOrder::Hue).unwrap();
+    assert_eq!(palette_strings(&hue), owned(&[("#fd0400", 8), ("#02667d", 12), ("#ffffff", 1)]));
+}
+
+#[test]
+fn test_palette_edge_cases() {
+    use pixels::{palette, Layout, PaletteOrder, Quantizer};
+
+    let quantizers = [Quantizer::MedianCut, Quantizer::KMeans { seed: 3, iterations: 5 }];
+    for quantizer in quantizers {
+        let rgba = [
+            255, 0, 0, 255,
+            0, 0, 255, 0,
+            255, 0, 0, 1,
+            0, 0, 0, 128,
+            128, 128, 128, 255,
+        ];
+        assert_eq!(
+            palette(&rgba, Layout::Rgba, 5, quantizer, PaletteOrder::Population),
+            Ok(vec![(Color::new_rgb(255, 0, 0), 2), (Color::new_rgb(0, 0, 0), 1), (Color::new_rgb(128, 128, 128), 1)]),
+        );
+        assert_eq!(
+            palette(&rgba, Layout::Rgba, 5, quantizer, PaletteOrder::Hue),
+            Ok(vec![(Color::new_rgb(255, 0, 0), 2), (Color::new_rgb(0, 0, 0), 1), (Color::new_rgb(128, 128, 128), 1)]),
+        );
+        assert_eq!(palette(&rgba, Layout::Rgba, 0, quantizer, PaletteOrder::Population), Ok(vec![]));
+        assert_eq!(palette(&[], Layout::Rgb, 3, quantizer, PaletteOrder::Population), Ok(vec![]));
+        assert_eq!(palette(&[0, 0, 255, 0], Layout::Rgba, 3, quantizer, PaletteOrder::Population), Ok(vec![]));
+        assert_eq!(palette(&rgba, Layout::Rgb, 3, quantizer, PaletteOrder::Population), Err(pixels::BufferError::PartialPixel));
+
+        let gray = [7, 7, 7, 7, 7, 7];
+        assert_eq!(palette(&gray, Layout::Rgb, 4, quantizer, PaletteOrder::Hue), Ok(vec![(Color::new_rgb(7, 7, 7), 2)]));
+    }
+}
+
+#[test]
+fn test_image_palette() {
+    use pixels::{Image, PaletteOrder, Quantizer};
+
+    let data: Vec<u8> = (0..64u32)
+        .flat_map(|i| {
+            let x = (i * 4) as u8;
+            if i % 2 == 0 { [x, 0, 0] } else { [0, x, 255 - x] }
+        })
+        .collect();
+    let image = Image::new(8, 8, data.clone()).unwrap();
+
+    for quantizer in [Quantizer::MedianCut, Quantizer::KMeans { seed: 11, iterations: 20 }] {
+        let colors = image.palette(5, quantizer, PaletteOrder::Population);
+        assert_eq!(colors.len(), 5);
+        assert_eq!(colors.iter().map(|&(_, n)| n).sum::<usize>(), 64);
+        assert!(colors.windows(2).all(|w| w[0].1 >= w[1].1));
+        assert_eq!(colors, pixels::palette(&data, pixels::Layout::Rgb, 5, quantizer, PaletteOrder::Population).unwrap());
+
+        let mut by_hue = image.palette(5, quantizer, PaletteOrder::Hue);
+        assert!(by_hue.windows(2).all(|w| w[0].0.to_hsv().unwrap_hsv().0 <= w[1].0.to_hsv().unwrap_hsv().0));
+        by_hue.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.unwrap_rgb().cmp(&b.0.unwrap_rgb())));
+        assert_eq!(by_hue, colors);
+    }
+}
+
+#[test]
+fn test_ansi_palette() {
+    assert_eq!(Color::from_ansi256(0), Color::new_rgb(0, 0, 0));
+    assert_eq!(Color::from_ansi256(4), Color::new_rgb(0, 0, 238));
+    assert_eq!(Color::from_ansi256(8), Color::new_rgb(127, 127, 127));
+    assert_eq!(Color::from_ansi256(12), Color::new_rgb(92, 92, 255));
+    assert_eq!(Color::from_ansi256(15), Color::new_rgb(255, 255, 255));
+    assert_eq!(Color::from_ansi256(16), Color::new_rgb(0, 0, 0));
+    assert_eq!(Color::from_ansi256(56), Color::new_rgb(95, 0, 215));
+    assert_eq!(Color::from_ansi256(196), Color::new_rgb(255, 0, 0));
+    assert_eq!(Color::from_ansi256(208), Color::new_rgb(255, 135, 0));
+    assert_eq!(Color::from_ansi256(231), Color::new_rgb(255, 255, 255));
+    assert_eq!(Color::from_ansi256(232), Color::new_rgb(8, 8, 8));
+    assert_eq!(Color::from_ansi256(244), Color::new_rgb(128, 128, 128));
+    assert_eq!(Color::from_ansi256(255), Color::new_rgb(238, 238, 238));
+
+    for i in 0..=255 {
+        let color = Color::from_ansi256(i);
+        if i >= 16 {
+            assert_eq!(color.to_ansi256(), i);
+        } else {
+            assert_eq!(color.to_ansi16(), i);
+        }
+    }
+}
+
+#[test]
+fn test_ansi_nearest() {
+    assert_eq!(Color::new_rgb(255, 0, 0).to_ansi256(), 196);
+    assert_eq!(Color::new_rgb(255, 0, 0).to_ansi16(), 9);
+    assert_eq!(Color::new_rgb(10, 128, 200).to_ansi256(), 32);
+    assert_eq!(Color::new_rgb(10, 128, 200).to_ansi16(), 12);
+    assert_eq!(Color::new_hsv(200, 50, 50).to_ansi256(), 24);
+    assert_eq!(Color::new_rgba(255, 128, 0, 10).to_ansi256(), 208);
+    assert_eq!(Color::new_rgb(128, 128, 128).to_ansi256(), 244);
+    assert_eq!(Color::new_rgb(128, 128, 128).to_ansi16(), 8);
+    assert_eq!(Color::new_rgb(250, 250, 250).to_ansi256(), 231);
+    assert_eq!(Color::new_rgb(250, 250, 250).to_ansi16(), 15);
+    assert_eq!(Color::new_rgb(12, 12, 12).to_ansi256(), 232);
+    assert_eq!(Color::new_rgb(12, 12, 12).to_ansi16(), 0);
+    assert_eq!(Color::new_rgb(100, 0, 200).to_ansi256(), 56);
+    assert_eq!(Color::new_rgb(100, 0, 200).to_ansi16(), 4);
+}
+
+#[test]
+fn test_to_ansi() {
+    let blue = Color::new_rgb(10, 128, 200);
+    assert_eq!(blue.to_ansi(AnsiMode::TrueColor, Layer::Foreground), "\x1b[38;2;10;128;200m");
+    assert_eq!(blue.to_ansi(AnsiMode::TrueColor, Layer::Background), "\x1b[48;2;10;128;200m");
+    assert_eq!(blue.to_ansi(AnsiMode::Ansi256, Layer::Foreground), "\x1b[38;5;32m");
+    assert_eq!(blue.to_ansi(AnsiMode::Ansi256, Layer::Background), "\x1b[48;5;32m");
+    assert_eq!(blue.to_ansi(AnsiMode::Ansi16, Layer::Foreground), "\x1b[94m");
+    assert_eq!(blue.to_ansi(AnsiMode::Ansi16, Layer::Background), "\x1b[104m");
+
+    let purple = Color::new_rgb(100, 0, 200);
+    assert_eq!(purple.to_ansi(AnsiMode::Ansi16, Layer::Foreground), "\x1b[34m");
+    assert_eq!(purple.to_ansi(AnsiMode::Ansi16, Layer::Background), "\x1b[44m");
+    assert_eq!(Color::new_rgb(0, 0, 0).to_ansi(AnsiMode::Ansi16, Layer::Foreground), "\x1b[30m");
+    assert_eq!(Color::new_rgb(255, 255, 255).to_ansi(AnsiMode::Ansi16, Layer::Background), "\x1b[107m");
+    assert_eq!(Color::new_rgb(0, 0, 0).to_ansi(AnsiMode::Ansi256, Layer::Foreground), "\x1b[38;5;16m");
+
+    assert_eq!(Color::new_hsv(200, 50, 50).to_ansi(AnsiMode::TrueColor, Layer::Foreground), "\x1b[38;2;64;106;128m");
+    assert_eq!(Color::new_rgba(255, 128, 0, 10).to_ansi(AnsiMode::TrueColor, Layer::Background), "\x1b[48;2;255;128;0m");
+    assert_eq!(Color::new_hsva(0, 100, 100, 0).to_ansi(AnsiMode::TrueColor, Layer::Foreground), "\x1b[38;2;255;0;0m");
+}
+
+#[test]
+fn test_swatches() {
+    let blue = Color::new_rgb(10, 128, 200);
+    let gray = Color::new_hsv(200, 50, 50);
+    assert_eq!(ANSI_RESET, "\x1b[0m");
+    assert_eq!(blue.swatch(AnsiMode::TrueColor), "\x1b[48;2;10;128;200m  \x1b[0m #0a80c8");
+    assert_eq!(gray.swatch(AnsiMode::Ansi256), "\x1b[48;5;24m  \x1b[0m hsv(200,50%,50%)");
+    assert_eq!(blue.swatch(AnsiMode::Ansi16), "\x1b[104m  \x1b[0m #0a80c8");
+
+    let mut out: Vec<u8> = vec![];
+    print_swatches(&mut out, &[blue, gray], AnsiMode::TrueColor).unwrap();
+    assert_eq!(
+        String::from_utf8(out).unwrap(),
+        "\x1b[48;2;10;128;200m  \x1b[0m #0a80c8\n\x1b[48;2;64;106;128m  \x1b[0m hsv(200,50%,50%)\n",
+    );
+
+    let mut out: Vec<u8> = vec![];
+    print_swatches(&mut out, &[], AnsiMode::Ansi16).unwrap();
+    assert!(out.is_empty());
+}
+
+#[test]
+fn test_lighten_darken() {
+    let red = Color::new_rgb(255, 0, 0);
+    assert_eq!(red.lighten(0.1), Color::new_rgb(255, 51, 51));
+    assert_eq!(red.darken(0.1), Color::new_rgb(204, 0, 0));
+    assert_eq!(red.lighten(1.0), Color::new_rgb(255, 255, 255));
+    assert_eq!(red.darken(1.0), Color::new_rgb(0, 0, 0));
+    assert_eq!(red.lighten(5.0), Color::new_rgb(255, 255, 255));
+    assert_eq!(red.lighten(-0.1), red.darken(0.1));
+    assert_eq!(red.lighten(f64::NAN), red);
+
+    let blue = Color::new_rgb(10, 128, 200);
+    assert_eq!(blue.lighten(0.1), Color::new_rgb(18, 158, 243));
+    assert_eq!(blue.darken(0.1), Color::new_rgb(8, 97, 151));
+    assert_eq!(Color::new_rgba(10, 128, 200, 77).lighten(0.1), Color::new_rgba(18, 158, 243, 77));
+    assert_eq!(Color::new_rgba(10, 128, 200, 77).lighten(1.0), Color::new_rgba(255, 255, 255, 77));
+
+    let hsl = Color::new_hsl(200, 50, 50);
+    assert_eq!(hsl.lighten(0.0), Color::new_rgb(64, 149, 191));
+    assert_eq!(hsl.lighten(0.1), Color::new_rgb(102, 170, 204));
+    assert_eq!(hsl.darken(0.1), Color::new_rgb(51, 119, 153));
+    assert_eq!(Color::new_hsv(120, 0, 50).lighten(0.1), Color::new_rgb(153, 153, 153));
+    assert_eq!(Color::new_rgb(0, 0, 0).lighten(0.1), Color::new_rgb(26, 26, 26));
+    assert_eq!(Color::new_rgb(0, 0, 0).darken(0.1), Color::new_rgb(0, 0, 0));
+}
+
+#[test]
+fn test_theme() {
+    let text = "# brand
+primary = #3366cc
+text = rgb(20, 20, 20)
+
+accent = primary.lighten(10%)
+  muted   =   mix( primary , text , 25% )
+inverse = invert(primary)
+half = mix(primary, white)
+hsl = HSL(200, 50%, 50%).darken(0.2).invert()
+link = accent.darken(5%)
+red = tomato
+warn = red
+glass = #3366CC80
+glass2 = glass.lighten(20%)
+";
+    let theme = Theme::parse(text).unwrap();
+    let expected = [
+        ("primary", "#3366cc"),
+        ("text", "#141414"),
+        ("accent", "#5c85d6"),
+        ("muted", "#2b529e"),
+        ("inverse", "#cc9933"),
+        ("half", "#99b3e6"),
+        ("hsl", "#d9a68c"),
+        ("link", "#4876d1"),
+        ("red", "#ff6347"),
+        ("warn", "#ff6347"),
+        ("glass", "#3366cc80"),
+        ("glass2", "#85a3e080"),
+    ];
+    let entries: Vec<(&str, String)> = theme.entries().iter().map(|(n, c)| (n.as_str(), c.to_rgb().to_string())).collect();
+    let expected: Vec<(&str, String)> = expected.iter().map(|&(n, c)| (n, c.to_string())).collect();
+    assert_eq!(entries, expected);
+
+    assert_eq!(theme.get("primary"), Some(Color::new_rgb(0x33, 0x66, 0xcc)));
+    assert_eq!(theme.get("text"), Some(Color::new_rgb(20, 20, 20)));
+    assert_eq!(theme.get("glass"), Some(Color::new_rgba(0x33, 0x66, 0xcc, 0x80)));
+    assert_eq!(theme.get("inverse"), Some(Color::new_rgb(0x33, 0x66, 0xcc).invert()));
+    assert_eq!(theme.get("hsl").map(|c| c.to_rgb()), Some(Color::new_hsl(200, 50, 50).darken(0.2).invert()));
+    assert_eq!(theme.get("Primary"), None);
+    assert_eq!(theme.get("tomato"), None);
+
+    assert_eq!(text.parse::<Theme>(), Ok(theme.clone()));
+    assert_eq!(
+        theme.to_css(),
+        ":root {
+  --primary: #3366cc;
+  --text: #141414;
+  --accent: #5c85d6;
+  --muted: #2b529e;
+  --inverse: #cc9933;
+  --half: #99b3e6;
+  --hsl: #d9a68c;
+  --link: #4876d1;
+  --red: #ff6347;
+  --warn: #ff6347;
+  --glass: #3366cc80;
+  --glass2: #85a3e080;
+}
+",
+    );
+}
+
+#[test]
+fn test_theme_forward_references() {
+    let theme = Theme::parse("a = b.invert()\nb = mix(c, #000, 0.5)\nc = #ffffff").unwrap();
+    assert_eq!(theme.get("c"), Some(Color::new_rgb(255, 255, 255)));
+    assert_eq!(theme.get("b"), Some(Color::new_rgb(128, 128, 128)));
+    assert_eq!(theme.get("a"), Some(Color::new_rgb(127, 127, 127)));
+    assert_eq!(theme.entries().iter().map(|(n, _)| n.as_str()).collect::<Vec<_>>(), vec!["a", "b", "c"]);
+
+    let theme = Theme::parse("bg = white.darken(1)\nfg = invert(bg).darken(0%)\nfg_2-b = fg").unwrap();
+    assert_eq!(theme.get("bg"), Some(Color::new_rgb(0, 0, 0)));
+    assert_eq!(theme.get("fg_2-b"), Some(Color::new_rgb(255, 255, 255)));
+
+    assert_eq!(Theme::parse("").unwrap().to_css(), ":root {\n}\n");
+    assert_eq!(Theme::parse("  # only a comment\n\n   \n").unwrap().entries(), &[]);
+}
+
+#[test]
+fn test_theme_errors() {
+    fn error(text: &str) -> (usize, ThemeErrorReason) {
+        let error = Theme::parse(text).unwrap_err();
+        assert!(error.to_string().contains(&error.line.to_string()));
+        (error.line, error.reason)
+    }
+    fn names(names: &[&str]) -> ThemeErrorReason {
+        ThemeErrorReason::Cycle(names.iter().map(|n| n.to_string()).collect())
+    }
+
+    assert_eq!(error("a = b\nb = c\nc = a"), (3, names(&["a", "b", "c"])));
+    assert_eq!(error("x = a\nb = a.lighten(10%)\na = mix(b, white)"), (2, names(&["a", "b"])));
+    assert_eq!(error("a = b.lighten(10%)\nb = mix(c, #000)\nc = invert(a)"), (3, names(&["a", "b", "c"])));
+    assert_eq!(error("ok = #fff\na = a.lighten(10%)"), (2, names(&["a"])));
+    assert_eq!(error("a = #fff\n\nb = nope"), (3, ThemeErrorReason::UnknownName("nope".to_string())));
+    assert_eq!(error("a = b\nb = mix(#fff, c)\nc = a"), (3, names(&["a", "b", "c"])));
+    assert_eq!(error("a = b\nb = mix(nope, a)"), (2, ThemeErrorReason::UnknownName("nope".to_string())));
+
+    let invalid = |position, reason| ThemeErrorReason::InvalidColor(ParseColorError { position, reason });
+    assert_eq!(error("a = #ggg"), (1, invalid(1, ParseColorReason::InvalidHexDigit)));
+    assert_eq!(error("# comment\na = rgb(300,0,0)"), (2, invalid(4, ParseColorReason::OutOfRange)));
+    assert_eq!(error("a = #ffff0"), (1, invalid(1, ParseColorReason::InvalidHexLength)));
+
+    assert_eq!(error("a = #fff.lighten(101%)"), (1, ThemeErrorReason::InvalidAmount));
+    assert_eq!(error("a = #fff.lighten(1.5)"), (1, ThemeErrorReason::InvalidAmount));
+    assert_eq!(error("a = #fff.darken(10)"), (1, ThemeErrorReason::InvalidAmount));
+    assert_eq!(error("a = mix(#fff, #000, 2)"), (1, ThemeErrorReason::InvalidAmount));
+    assert_eq!(error("a = #fff\nb = #000\na = #000"), (3, ThemeErrorReason::Duplicate("a".to_string())));
+
+    for text in [
+        "a #fff",
+        "1a = #fff",
+        "a = ",
+        "a = mix(#fff)",
+        "a = mix(#fff, #000,)",
+        "a = #fff.foo(1)",
+        "a = #fff.lighten",
+        "a = #fff.lighten()",
+        "a = #fff.invert(1)",
+        "a = #fff trailing",
+        "a = foo(1)",
+        "a = #fff.lighten(.5)",
+        "a = #fff.lighten(5.)",
+        "a = #fff.lighten(0.1 %)",
+        "a = invert(#fff",
+        "a = rgb(1, 2, 3",
+        "a = #fff # comment",
+    ] {
+        assert_eq!(error(text), (1, ThemeErrorReason::Syntax), "{:?}", text);
+    }
+
+    // format errors come before unknown names, even on a later line
+    assert_eq!(error("a = nope\nb = #fff.lighten(2)"), (2, ThemeErrorReason::InvalidAmount));
+}
+
+fn assert_channels(color: FloatColor, space: ColorSpace, expected: [f64; 3], epsilon: f64) {
+    assert_eq!(color.space, space);
+    for (&channel, &expected) in color.channels.iter().zip(&expected) {
+        assert_close(channel, expected, epsilon);
+    }
+}
+
+#[test]
+fn test_float_color_conversions() {
+    let red = FloatColor::from(Color::new_rgb(255, 0, 0));
+    assert_eq!(red, FloatColor { space: ColorSpace::Srgb, channels: [1.0, 0.0, 0.0], alpha: 1.0 });
+
+    assert_eq!(red.convert(ColorSpace::Srgb), red);
+    assert_eq!(red.convert(ColorSpace::SrgbLinear).channels, [1.0, 0.0, 0.0]);
+    assert_channels(red.convert(ColorSpace::DisplayP3), ColorSpace::DisplayP3, [0.917552, 0.200304, 0.138573], 1e-5);
+    assert_channels(red.convert(ColorSpace::Rec2020), ColorSpace::Rec2020, [0.792041, 0.231000, 0.073773], 1e-5);
+    assert_channels(red.convert(ColorSpace::XyzD65), ColorSpace::XyzD65, [0.4124564, 0.2126729, 0.0193339], 1e-7);
+    assert_channels(red.convert(ColorSpace::XyzD50), ColorSpace::XyzD50, [0.436075, 0.222505, 0.013932], 1e-5);
+
+    let white = FloatColor::from(Color::new_rgb(255, 255, 255));
+    assert_channels(white.convert(ColorSpace::XyzD65), ColorSpace::XyzD65, [0.95047, 1.0, 1.08883], 1e-6);
+    assert_channels(white.convert(ColorSpace::XyzD50), ColorSpace::XyzD50, [0.96422, 1.0, 0.82521], 1e-6);
+    // the 7-digit sRGB matrix and the ones from CSS don't agree exactly on white
+    assert_channels(white.convert(ColorSpace::DisplayP3), ColorSpace::DisplayP3, [1.0, 1.0, 1.0], 1e-3);
+    assert_channels(white.convert(ColorSpace::Rec2020), ColorSpace::Rec2020, [1.0, 1.0, 1.0], 1e-3);
+
+    let blue = FloatColor::from(Color::new_rgba(10, 128, 200, 51));
+    assert_eq!(blue.alpha, 0.2);
+    let p3 = blue.convert(ColorSpace::DisplayP3);
+    assert_channels(p3, ColorSpace::DisplayP3, [0.223257, 0.494287, 0.762026], 1e-5);
+    assert_eq!(p3.alpha, 0.2);
+    assert_channels(blue.convert(ColorSpace::XyzD50), ColorSpace::XyzD50, [0.167084, 0.190432, 0.433496], 1e-5);
+    assert_channels(p3.convert(ColorSpace::XyzD50).convert(ColorSpace::Srgb), ColorSpace::Srgb, blue.channels, 1e-6);
+
+    let xyz: Xyz = blue.into();
+    assert_components!(xyz, x: Color::new_rgb(10, 128, 200).to_xyz().x, y: Color::new_rgb(10, 128, 200).to_xyz().y, z: Color::new_rgb(10, 128, 200).to_xyz().z, 1e-12);
+    let back = FloatColor::from(xyz);
+    assert_eq!((back.space, back.alpha), (ColorSpace::XyzD65, 1.0));
+}
+
+#[test]
+fn test_float_color_wide_gamut() {
+    let p3_red = FloatColor { space: ColorSpace::DisplayP3, channels: [1.0, 0.0, 0.0], alpha: 0.5 };
+    assert!(!p3_red.in_gamut());
+    let srgb = p3_red.convert(ColorSpace::Srgb);
+    assert_channels(srgb, ColorSpace::Srgb, [1.092990, -0.226745, -0.150155], 1e-5);
+    assert_eq!(srgb.alpha, 0.5);
+    assert_channels(p3_red.convert(ColorSpace::Rec2020), ColorSpace::Rec2020, [0.868733, 0.175028, -0.005447], 1e-5);
+    assert_eq!(p3_red.to_color(GamutMapping::Clip), Color::new_rgba(255, 0, 0, 128));
+    assert_eq!(p3_red.to_color(GamutMapping::ReduceChroma), Color::new_rgba(255, 52, 40, 128));
+
+    let green = FloatColor { space: ColorSpace::Rec2020, channels: [0.0, 1.0, 0.0], alpha: 1.0 };
+    assert!(!green.in_gamut());
+    assert_channels(green.convert(ColorSpace::DisplayP3), ColorSpace::DisplayP3, [-0.567737, 1.032607, -0.149964], 1e-5);
+    assert_channels(green.convert(ColorSpace::Srgb), ColorSpace::Srgb, [-0.790319, 1.056313, -0.350204], 1e-5);
+    assert_eq!(green.to_color(GamutMapping::Clip), Color::new_rgb(0, 255, 0));
+    assert_eq!(green.to_color(GamutMapping::ReduceChroma), Color::new_rgb(0, 237, 126));
+
+    let hdr = FloatColor { space: ColorSpace::SrgbLinear, channels: [2.0, 1.0, -0.5], alpha: 1.0 };
+    assert_channels(hdr.convert(ColorSpace::Srgb), ColorSpace::Srgb, [1.353256, 1.0, -0.735357], 1e-6);
+    assert_channels(hdr.convert(ColorSpace::Rec2020).convert(ColorSpace::SrgbLinear), ColorSpace::SrgbLinear, [2.0, 1.0, -0.5], 1e-6);
+    assert_channels(hdr.convert(ColorSpace::Srgb).convert(ColorSpace::SrgbLinear), ColorSpace::SrgbLinear, [2.0, 1.0, -0.5], 1e-12);
+    assert!(!hdr.in_gamut());
+    assert_eq!(hdr.to_color(GamutMapping::Clip), Color::new_rgb(255, 255, 0));
+
+    let inside = FloatColor { space: ColorSpace::Srgb, channels: [0.4, 0.4, 0.4], alpha: 2.0 };
+    assert!(inside.in_gamut());
+    assert_eq!(inside.to_color(GamutMapping::Clip), Color::new_rgb(102, 102, 102));
+    assert_eq!(FloatColor { alpha: -1.0, ..inside }.to_color(GamutMapping::Clip), Color::new_rgba(102, 102, 102, 0));
+}
+
+#[test]
+fn test_float_color_round_trip() {
+    let spaces = [
+        ColorSpace::Srgb,
+        ColorSpace::SrgbLinear,
+        ColorSpace::DisplayP3,
+        ColorSpace::Rec2020,
+        ColorSpace::XyzD65,
+        ColorSpace::XyzD50,
+    ];
+    for r in (0..=255).step_by(15) {
+        for g in (0..=255).step_by(17) {
+            for b in (0..=255).step_by(51) {
+                for color in [Color::new_rgb(r, g, b), Color::new_rgba(r, g, b, b / 2)] {
+                    let float = FloatColor::from(color);
+                    assert_eq!(float.to_color(GamutMapping::Clip), color);
+                    for space in spaces {
+                        let converted = float.convert(space);
+                        assert!(converted.in_gamut());
+                        assert_eq!(converted.to_color(GamutMapping::Clip), color);
+                        assert_eq!(converted.to_color(GamutMapping::ReduceChroma), color);
+                        assert_channels(converted.convert(ColorSpace::Srgb), ColorSpace::Srgb, float.channels, 1e-5);
+                        assert_eq!(FloatColor::parse(&converted.to_string()), Ok(converted));
+                    }
+                }
+            }
+        }
+    }
+    assert_eq!(FloatColor::from(Color::new_hsv(200, 50, 50)).to_color(GamutMapping::Clip), Color::new_rgb(64, 106, 128));
+}
+
+#[test]
+fn test_float_color_css() {
+    let names: Vec<&str> = [
+        ColorSpace::Srgb,
+        ColorSpace::SrgbLinear,
+        ColorSpace::DisplayP3,
+        ColorSpace::Rec2020,
+        ColorSpace::XyzD65,
+        ColorSpace::XyzD50,
+    ]
+    .iter()
+    .map(|s| s.css_name())
+    .collect();
+    assert_eq!(names, vec!["srgb", "srgb-linear", "display-p3", "rec2020", "xyz-d65", "xyz-d50"]);
+
+    let p3 = FloatColor { space: ColorSpace::DisplayP3, channels: [1.0, 0.5, 0.0], alpha: 1.0 };
+    assert_eq!(p3.to_string(), "color(display-p3 1 0.5 0)");
+    assert_eq!(FloatColor { alpha: 0.25, ..p3 }.to_string(), "color(display-p3 1 0.5 0 / 0.25)");
+    let odd = FloatColor { space: ColorSpace::XyzD50, channels: [-0.5, 0.0000001, 1234.5], alpha: 0.0 };
+    assert_eq!(odd.to_string(), "color(xyz-d50 -0.5 0.0000001 1234.5 / 0)");
+
+    assert_eq!(FloatColor::parse("color(display-p3 1 0.5 0)"), Ok(p3));
+    assert_eq!(" COLOR( Display-P3 100% 50% 0% / 25% ) ".parse::<FloatColor>(), Ok(FloatColor { alpha: 0.25, ..p3 }));
+    assert_eq!(
+        FloatColor::parse("color(xyz 0.1 0.2 0.3)"),
+        Ok(FloatColor { space: ColorSpace::XyzD65, channels: [0.1, 0.2, 0.3], alpha: 1.0 }),
+    );
+    assert_eq!(
+        FloatColor::parse("color(srgb-linear -0.5 +1.5 1e-3/0.5)"),
+        Ok(FloatColor { space: ColorSpace::SrgbLinear, channels: [-0.5, 1.5, 0.001], alpha: 0.5 }),
+    );
+    assert_eq!(FloatColor::parse("color(rec2020 .5 0 0)").map(|c| c.channels), Ok([0.5, 0.0, 0.0]));
+    assert_eq!(FloatColor::parse(&odd.to_string()), Ok(odd));
+
+    let error = |s: &str| FloatColor::parse(s).map_err(|e| (e.position, e.reason));
+    assert_eq!(error(""), Err((0, ParseColorReason::Empty)));
+    assert_eq!(error("   "), Err((0, ParseColorReason::Empty)));
+    assert_eq!(error(" rgb(1,2,3)"), Err((1, ParseColorReason::UnknownFormat)));
+    assert_eq!(error("color(foo 1 0 0)"), Err((6, ParseColorReason::UnknownFormat)));
+    assert_eq!(error("color(srgb1 0 0 0)"), Err((6, ParseColorReason::UnknownFormat)));
+    assert_eq!(error("color (srgb 1 0 0)"), Err((5, ParseColorReason::Expected('('))));
+    assert_eq!(error("color"), Err((5, ParseColorReason::Expected('('))));
+    assert_eq!(error("color(srgb 1 0 0"), Err((16, ParseColorReason::Expected(')'))));
+    assert_eq!(error("color(srgb 1 0 0 / 0.5 "), Err((23, ParseColorReason::Expected(')'))));
+    assert_eq!(error("color(srgb 1 0 0 0)"), Err((17, ParseColorReason::Expected(')'))));
+    assert_eq!(error("color(srgb 1 0)"), Err((14, ParseColorReason::InvalidNumber)));
+    assert_eq!(error("color(srgb 1 0 x)"), Err((15, ParseColorReason::InvalidNumber)));
+    assert_eq!(error("color(srgb nan 0 0)"), Err((11, ParseColorReason::InvalidNumber)));
+    assert_eq!(error("color(srgb inf 0 0)"), Err((11, ParseColorReason::InvalidNumber)));
+    assert_eq!(error("color(srgb 1 0 0 /)"), Err((18, ParseColorReason::InvalidNumber)));
+    assert_eq!(error("color(srgb 1 0 5 %)"), Err((17, ParseColorReason::Expected(')'))));
+    assert_eq!(error("color(srgb 1 0 0 / 2)"), Err((19, ParseColorReason::OutOfRange)));
+    assert_eq!(error("color(srgb 1 0 0 / -1%)"), Err((19, ParseColorReason::OutOfRange)));
+    assert_eq!(error("color(srgb 1 0 0) x"), Err((18, ParseColorReason::TrailingCharacters)));
+}
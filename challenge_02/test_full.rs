@@ -153,3 +153,316 @@ fn test_multiplying_matrices_2() {
 
     assert_eq!(matrix1 * matrix2, String::from("едно  евдевдевд  иритеч иритеч"));
 }
+
+#[test]
+fn test_adding_string_and_int() {
+    assert_eq!((Cell(String::from("badger")) + Cell(4)).0, String::from("4 badger"));
+    assert_eq!((Cell(String::from("badger")) + Cell(0)).0, String::from("0 badger"));
+    assert_eq!((Cell(String::from("badger")) + Cell(-2)).0, String::from("regdab 2"));
+    assert_eq!((Cell(String::from("опа")) + Cell(-3)).0, String::from("апо 3"));
+    assert_eq!((Cell(String::from("")) + Cell(573)).0, String::from("573 "));
+
+    for n in [-100, -1, 0, 1, 42] {
+        let word = Cell(String::from("едно две"));
+        assert_eq!(word.clone() + Cell(n), Cell(n) + word);
+    }
+}
+
+#[test]
+fn test_multiplying_string_and_int() {
+    assert_eq!((Cell(String::from("badger")) * Cell(4)).0, String::from("badgerbadgerbadgerbadger"));
+    assert_eq!((Cell(String::from("badger")) * Cell(0)).0, String::from(""));
+    assert_eq!((Cell(String::from("woo!")) * Cell(-2)).0, String::from("!oow!oow"));
+    assert_eq!((Cell(String::from("опа")) * Cell(-3)).0, String::from("апоапоапо"));
+
+    for n in [-5, -1, 0, 1, 7] {
+        let word = Cell(String::from("аб,c"));
+        assert_eq!(word.clone() * Cell(n), Cell(n) * word);
+    }
+}
+
+#[test]
+fn test_negative_extremes() {
+    assert_eq!((Cell(i32::MIN) + Cell(String::from("ab"))).0, String::from("ba 2147483648"));
+    assert_eq!((Cell(String::from("ab")) + Cell(i32::MIN)).0, String::from("ba 2147483648"));
+    assert_eq!((Cell(i32::MAX) + Cell(String::from("ab"))).0, String::from("2147483647 ab"));
+    assert_eq!((Cell(i32::MIN) * Cell(String::new())).0, String::new());
+}
+
+#[test]
+fn test_same_type_cells() {
+    assert_eq!(Cell(2) + Cell(3), Cell(5));
+    assert_eq!(Cell(2) - Cell(3), Cell(-1));
+    assert_eq!(Cell(-4) * Cell(3), Cell(-12));
+    assert_eq!(-Cell(5), Cell(-5));
+    assert_eq!(-Cell(-5), Cell(5));
+
+    assert_eq!(Cell(1.5) + Cell(0.25), Cell(1.75));
+    assert_eq!(Cell(1.5) * Cell(2.0), Cell(3.0));
+    assert_eq!(-Cell(0.5), Cell(-0.5));
+    assert_eq!(Cell(10u8) - Cell(3u8), Cell(7u8));
+}
+
+#[test]
+fn test_assign_operators() {
+    let mut cell = Cell(10);
+    cell += Cell(5);
+    assert_eq!(cell, Cell(15));
+    cell -= Cell(20);
+    assert_eq!(cell, Cell(-5));
+    cell *= Cell(-3);
+    assert_eq!(cell, Cell(15));
+    cell += &Cell(1);
+    cell -= &Cell(2);
+    cell *= &Cell(2);
+    assert_eq!(cell, Cell(28));
+
+    let mut word = Cell(String::from("badger"));
+    word *= Cell(2);
+    assert_eq!(word, Cell(String::from("badgerbadger")));
+    word += Cell(-1);
+    assert_eq!(word, Cell(String::from("regdabregdab 1")));
+    word *= &Cell(-1);
+    assert_eq!(word, Cell(String::from("1 badgerbadger")));
+    word += &Cell(0);
+    assert_eq!(word, Cell(String::from("0 1 badgerbadger")));
+}
+
+#[test]
+fn test_reference_operators() {
+    let number = Cell(3);
+    let negative = Cell(-2);
+    let word = Cell(String::from("boom!"));
+
+    assert_eq!(&number * &word, Cell(String::from("boom!boom!boom!")));
+    assert_eq!(&word * &number, Cell(String::from("boom!boom!boom!")));
+    assert_eq!(&number + &word, Cell(String::from("3 boom!")));
+    assert_eq!(&word + &number, Cell(String::from("3 boom!")));
+    assert_eq!(&negative + &word, Cell(String::from("!moob 2")));
+    assert_eq!(&negative * &word, Cell(String::from("!moob!moob")));
+    assert_eq!(&word + &negative, &negative + &word);
+
+    assert_eq!(&number + &negative, Cell(1));
+    assert_eq!(&number - &negative, Cell(5));
+    assert_eq!(&number * &negative, Cell(-6));
+    assert_eq!(-&negative, Cell(2));
+    assert_eq!(&Cell(0.5) * &Cell(4.0), Cell(2.0));
+
+    // nothing was moved
+    assert_eq!(number, Cell(3));
+    assert_eq!(negative, Cell(-2));
+    assert_eq!(word, Cell(String::from("boom!")));
+}
+
+#[test]
+fn test_graphemes_basic() {
+    assert_eq!(graphemes(""), Vec::<&str>::new());
+    assert_eq!(graphemes("abc"), vec!["a", "b", "c"]);
+    assert_eq!(graphemes("опа"), vec!["о", "п", "а"]);
+    assert_eq!(graphemes("a\r\nb\n\r"), vec!["a", "\r\n", "b", "\n", "\r"]);
+    assert_eq!(graphemes("\r\u{301}"), vec!["\r", "\u{301}"]);
+    assert_eq!(graphemes("\u{600}1"), vec!["\u{600}1"]);
+    assert_eq!(graphemes("\u{928}\u{93F}\u{930}"), vec!["\u{928}\u{93F}", "\u{930}"]);
+    assert_eq!(graphemes("\u{1100}\u{1161}\u{11A8}\u{AC00}\u{11A8}\u{AC01}\u{1161}"), vec!["\u{1100}\u{1161}\u{11A8}", "\u{AC00}\u{11A8}", "\u{AC01}", "\u{1161}"]);
+
+    for s in ["", "abc", "e\u{301}\u{302}x", "👨\u{200D}👩\u{200D}👧🇧🇬🇺", "a\r\n\u{200D}"] {
+        assert_eq!(graphemes(s).concat(), s);
+    }
+}
+
+#[test]
+fn test_graphemes_combining_marks() {
+    assert_eq!(graphemes("cafe\u{301}"), vec!["c", "a", "f", "e\u{301}"]);
+    assert_eq!(graphemes("Z\u{35A}\u{34E}\u{329}a\u{31C}"), vec!["Z\u{35A}\u{34E}\u{329}", "a\u{31C}"]);
+    assert_eq!(graphemes("\u{301}a"), vec!["\u{301}", "a"]);
+    assert_eq!(graphemes("и\u{306}"), vec!["и\u{306}"]);
+
+    assert_eq!(reverse_graphemes("cafe\u{301}"), "e\u{301}fac");
+    assert_eq!(reverse_graphemes("Z\u{35A}a\u{31C}!"), "!a\u{31C}Z\u{35A}");
+    assert_eq!(reverse_graphemes("опа"), "апо");
+    assert_eq!(reverse_graphemes("a\r\nb"), "b\r\na");
+}
+
+#[test]
+fn test_graphemes_emoji() {
+    let family = "👨\u{200D}👩\u{200D}👧";
+    assert_eq!(graphemes(&format!("{}x", family)), vec![family, "x"]);
+    assert_eq!(graphemes("❤\u{FE0F}\u{200D}🔥"), vec!["❤\u{FE0F}\u{200D}🔥"]);
+    assert_eq!(graphemes("👍🏽!"), vec!["👍🏽", "!"]);
+    assert_eq!(graphemes("1\u{FE0F}\u{20E3}2"), vec!["1\u{FE0F}\u{20E3}", "2"]);
+    assert_eq!(graphemes("a\u{200D}👩"), vec!["a\u{200D}", "👩"]);
+    assert_eq!(graphemes("👨\u{200D}\u{200D}👩"), vec!["👨\u{200D}\u{200D}", "👩"]);
+
+    assert_eq!(graphemes("🇧🇬🇺🇦"), vec!["🇧🇬", "🇺🇦"]);
+    assert_eq!(graphemes("🇧🇬🇺🇦🇧"), vec!["🇧🇬", "🇺🇦", "🇧"]);
+    assert_eq!(graphemes("🇧x🇬🇺"), vec!["🇧", "x", "🇬🇺"]);
+
+    assert_eq!(reverse_graphemes(&format!("{}👍🏽", family)), format!("👍🏽{}", family));
+    assert_eq!(reverse_graphemes("🇧🇬🇺🇦"), "🇺🇦🇧🇬");
+    assert_eq!(reverse_graphemes("🇧🇬🇺🇦🇧"), "🇧🇺🇦🇧🇬");
+}
+
+#[test]
+fn test_grapheme_cell_operations() {
+    let word = Cell(String::from("cafe\u{301}"));
+    assert_eq!(Cell(-1).add_graphemes(&word), Cell(String::from("e\u{301}fac 1")));
+    assert_eq!(Cell(3).add_graphemes(&word), Cell(3) + word.clone());
+    assert_eq!(Cell(0).add_graphemes(&word), Cell(String::from("0 cafe\u{301}")));
+    assert_eq!(Cell(-2).mul_graphemes(&word), Cell(String::from("e\u{301}face\u{301}fac")));
+    assert_eq!(Cell(2).mul_graphemes(&word), Cell(2) * word.clone());
+    assert_eq!(Cell(0).mul_graphemes(&word), Cell(String::new()));
+
+    let flags = Cell(String::from("🇧🇬🇺🇦"));
+    assert_eq!(Cell(-2).mul_graphemes(&flags), Cell(String::from("🇺🇦🇧🇬🇺🇦🇧🇬")));
+    assert_eq!(Cell(-1).add_graphemes(&flags), Cell(String::from("🇺🇦🇧🇬 1")));
+    assert_eq!(Cell(i32::MIN).add_graphemes(&Cell(String::from("👍🏽!"))), Cell(String::from("!👍🏽 2147483648")));
+
+    // the operators still reverse by `char`
+    assert_eq!((Cell(-1) + flags.clone()).0, String::from("🇦🇺🇬🇧 1"));
+    assert_eq!((Cell(-1) + Cell(String::from("опа"))), Cell(-1).add_graphemes(&Cell(String::from("опа"))));
+    assert_eq!(word, Cell(String::from("cafe\u{301}")));
+}
+
+#[test]
+fn test_checked_mul() {
+    let boom = Cell(String::from("boom!"));
+    assert_eq!(Cell(2).checked_mul(&boom, 10), Ok(Cell(String::from("boom!boom!"))));
+    assert_eq!(Cell(2).checked_mul(&boom, 100), Ok(Cell(2) * boom.clone()));
+    assert_eq!(Cell(3).checked_mul(&boom, 10), Err(RepeatError::TooLarge { needed: 15, budget: 10 }));
+    assert_eq!(Cell(-2).checked_mul(&boom, 10), Ok(Cell(String::from("!moob!moob"))));
+    assert_eq!(Cell(-2).checked_mul(&boom, 9), Err(RepeatError::TooLarge { needed: 10, budget: 9 }));
+
+    assert_eq!(Cell(0).checked_mul(&boom, 0), Ok(Cell(String::new())));
+    assert_eq!(Cell(i32::MAX).checked_mul(&Cell(String::new()), 0), Ok(Cell(String::new())));
+    assert_eq!(Cell(i32::MIN).checked_mul(&Cell(String::new()), 0), Ok(Cell(String::new())));
+
+    // bytes, not chars
+    let word = Cell(String::from("опа"));
+    assert_eq!(Cell(-2).checked_mul(&word, 12), Ok(Cell(String::from("апоапо"))));
+    assert_eq!(Cell(-2).checked_mul(&word, 11), Err(RepeatError::TooLarge { needed: 12, budget: 11 }));
+
+    assert_eq!(boom, Cell(String::from("boom!")));
+}
+
+#[cfg(target_pointer_width = "64")]
+#[test]
+fn test_checked_mul_64bit() {
+    assert_eq!(
+        Cell(i32::MAX).checked_mul(&Cell(String::from("boom!")), 1 << 20),
+        Err(RepeatError::TooLarge { needed: 5 * i32::MAX as usize, budget: 1 << 20 })
+    );
+    assert_eq!(
+        Cell(i32::MIN).checked_mul(&Cell(String::from("ab")), 1000),
+        Err(RepeatError::TooLarge { needed: 4294967296, budget: 1000 })
+    );
+    assert_eq!(Cell(i32::MIN).repeat(&Cell(String::from("ab"))).byte_len(), Some(4294967296));
+}
+
+#[cfg(target_pointer_width = "32")]
+#[test]
+fn test_checked_mul_overflow() {
+    let abc = Cell(String::from("abc"));
+    assert_eq!(Cell(i32::MAX).checked_mul(&abc, usize::MAX), Err(RepeatError::Overflow));
+    assert_eq!(Cell(i32::MIN).checked_mul(&abc, usize::MAX), Err(RepeatError::Overflow));
+    assert_eq!(Cell(i32::MAX).repeat(&abc).byte_len(), None);
+}
+
+#[test]
+fn test_lazy_repeat() {
+    let boom = Cell(String::from("boom!"));
+    assert_eq!(Cell(3).repeat(&boom).to_string(), "boom!boom!boom!");
+    assert_eq!(Cell(-3).repeat(&Cell(String::from(",regdab"))).to_string(), "badger,badger,badger,");
+    assert_eq!(Cell(0).repeat(&boom).to_string(), "");
+    assert_eq!(Cell(5).repeat(&Cell(String::new())).count(), 0);
+    assert_eq!(format!("[{}]", Cell(2).repeat(&boom)), "[boom!boom!]");
+
+    for n in [-3, -1, 0, 1, 2, 7] {
+        for s in ["boom!", "опа", "", "x"] {
+            let cell = Cell(String::from(s));
+            let expected = (Cell(n) * cell.clone()).0;
+            assert_eq!(Cell(n).repeat(&cell).collect::<String>(), expected);
+            assert_eq!(Cell(n).repeat(&cell).to_string(), expected);
+            assert_eq!(Cell(n).repeat(&cell).byte_len(), Some(expected.len()));
+        }
+    }
+}
+
+#[test]
+fn test_lazy_repeat_huge() {
+    let boom = Cell(String::from("boom!"));
+    assert_eq!(Cell(i32::MAX).repeat(&boom).take(7).collect::<String>(), "boom!bo");
+    assert_eq!(Cell(i32::MIN).repeat(&boom).take(12).collect::<String>(), "!moob!moob!m");
+    assert_eq!(Cell(i32::MIN).repeat(&Cell(String::from("опа"))).nth(4), Some('п'));
+
+    let word = Cell(String::from("опа"));
+    let mut partial = Cell(2).repeat(&word);
+    assert_eq!(partial.next(), Some('о'));
+    assert_eq!(partial.byte_len(), Some(10));
+    assert_eq!(partial.to_string(), "паопа");
+    assert_eq!(partial.collect::<String>(), "паопа");
+}
+
+#[test]
+fn test_any_size_iteration() {
+    let matrix = Matrix::from_rows([[1, 2, 3], [4, 5, 6]]);
+    assert_eq!(matrix.by_row(), i32_cell_vec![1, 2, 3, 4, 5, 6]);
+    assert_eq!(matrix.by_col(), i32_cell_vec![1, 4, 2, 5, 3, 6]);
+
+    let matrix = Matrix::from_rows([["a", "b"], ["c", "d"], ["e", "f"]].map(|row| row.map(String::from)));
+    assert_eq!(matrix.by_row(), string_cell_vec!["a", "b", "c", "d", "e", "f"]);
+    assert_eq!(matrix.by_col(), string_cell_vec!["a", "c", "e", "b", "d", "f"]);
+
+    assert_eq!(Matrix::from_rows([[1, 2, 3, 4]]).by_col(), i32_cell_vec![1, 2, 3, 4]);
+    assert_eq!(Matrix::from_rows([[1], [2], [3], [4]]).by_row(), i32_cell_vec![1, 2, 3, 4]);
+    assert_eq!(Matrix::from_rows([[7]]).by_col(), i32_cell_vec![7]);
+
+    assert_eq!(Matrix::<i32, 2, 0>::from_rows([[], []]).by_row(), Vec::<Cell<i32>>::new());
+    assert_eq!(Matrix::<i32, 0, 3>::from_rows([]).by_col(), Vec::<Cell<i32>>::new());
+}
+
+#[test]
+fn test_any_size_two_by_two() {
+    let rows = Matrix::from_rows([[1, 2], [3, 4]]);
+    let new = Matrix::new(&[1, 2, 3, 4]);
+    assert_eq!(rows.by_row(), new.by_row());
+    assert_eq!(rows.by_col(), new.by_col());
+
+    let strings = Matrix::from_rows([["one", "two"], ["three", "you get it"]].map(|row| row.map(String::from)));
+    assert_eq!(
+        Matrix::from_rows([[1, 2], [3, 1]]) * strings,
+        String::from("one threethree twotwotwo you get it")
+    );
+}
+
+#[test]
+fn test_any_size_adding() {
+    let left = Matrix::from_rows([[1, 0, -2]]);
+    let right = Matrix::from_rows([["one", "zero", "owt"]].map(|row| row.map(String::from)));
+    assert_eq!((left + right).by_col(), string_cell_vec!["1 one", "0 zero", "two 2"]);
+
+    let left = Matrix::from_rows([[1], [-3], [0]]);
+    let right = Matrix::from_rows([["a"], ["bc"], ["d"]].map(|row| row.map(String::from)));
+    let sum: Matrix<String, 3, 1> = left + right;
+    assert_eq!(sum.by_row(), string_cell_vec!["1 a", "cb 3", "0 d"]);
+}
+
+#[test]
+fn test_any_size_multiplying() {
+    let left = Matrix::from_rows([[1, 2, 3], [2, 1, 3]]);
+    let right = Matrix::from_rows([["a", "b"], ["c", "d"], ["e", "f"]].map(|row| row.map(String::from)));
+    assert_eq!(left * right, String::from("a cc eee bb d fff"));
+
+    let left = Matrix::from_rows([[1, 2], [3, -1], [0, 2]]);
+    let right = Matrix::from_rows([["a", "b", "c"], ["d", "ef", "g"]].map(|row| row.map(String::from)));
+    assert_eq!(left * right, String::from("a dd bbb fe  gg"));
+
+    let row = Matrix::from_rows([[2, -1, 1, 0]]);
+    let col = Matrix::from_rows([["ab"], ["cd"], ["e"], ["f"]].map(|row| row.map(String::from)));
+    assert_eq!(row * col, String::from("abab dc e "));
+
+    let col = Matrix::from_rows([[3], [1]]);
+    let row = Matrix::from_rows([["x", "y"]].map(|row| row.map(String::from)));
+    assert_eq!(col * row, String::from("xxx y"));
+
+    assert_eq!(Matrix::<i32, 2, 0>::from_rows([[], []]) * Matrix::<String, 0, 2>::from_rows([]), String::new());
+}